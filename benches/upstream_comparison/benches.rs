@@ -54,3 +54,8 @@ fn deserialize_struct_long_parameters_2() {
     )
     .unwrap();
 }
+
+#[divan::bench]
+fn deserialize_struct_unknown_fields() {
+    from_str::<StructForm>("utm_source=mail&foo=value&utm_medium=link").unwrap();
+}
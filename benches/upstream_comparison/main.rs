@@ -43,6 +43,6 @@ mod serde_html_form {
 
 #[cfg(not(rust_analyzer))]
 mod serde_urlencoded {
-    use serde_html_form::from_str;
+    use serde_urlencoded::from_str;
     include!("benches.rs");
 }
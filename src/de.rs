@@ -1,21 +1,60 @@
 //! Deserialization support for the `application/x-www-form-urlencoded` format.
 
-use form_urlencoded::{parse, Parse as UrlEncodedParse};
-use indexmap::map::{self, IndexMap};
+use alloc::{
+    borrow::Cow,
+    vec::{self, Vec},
+};
+use core::marker::PhantomData;
+
+use form_urlencoded::Parse as UrlEncodedParse;
 use serde_core::{
     de::{self, value::MapDeserializer, Deserialize},
     forward_to_deserialize_any,
 };
 
-#[doc(inline)]
-pub use serde_core::de::value::Error;
-
+#[cfg(feature = "encoding")]
+mod charset;
+mod checkbox;
+mod collect;
 pub mod empty_as_none;
+mod entries;
+mod error;
+mod form_errors;
+mod key_map;
+mod limits;
+mod nested;
+mod parser;
 mod part;
+mod placeholder;
+mod raw;
+mod rows;
+#[cfg(feature = "async")]
+mod stream;
 mod utils;
 mod val_or_vec;
 
-use self::{empty_as_none::EmptyAsNone, part::Part, val_or_vec::ValOrVec};
+use self::{
+    checkbox::CheckboxVisitor,
+    empty_as_none::EmptyAsNone,
+    entries::{Entries, UniqueEntries},
+    key_map::KeyMap,
+    limits::Checked,
+    part::Part,
+    raw::{RawPairs, StrPairs},
+    rows::RowsVisitor,
+    utils::struct_fields,
+    val_or_vec::{ValOrVec, Values},
+};
+use crate::KeyNotation;
+
+#[cfg(feature = "async")]
+pub use self::stream::{from_async_read, from_stream};
+pub use self::{
+    error::{Error, ErrorKind},
+    form_errors::{FieldError, FormErrors},
+    limits::Limits,
+    parser::Parser,
+};
 
 /// Deserializes a `application/x-www-form-urlencoded` value from a `&[u8]`.
 ///
@@ -62,7 +101,114 @@ pub fn from_str<'de, T>(input: &'de str) -> Result<T, Error>
 where
     T: Deserialize<'de>,
 {
-    from_bytes(input.as_bytes())
+    T::deserialize(Deserializer::with_input(Input::Str(input)))
+}
+
+/// Deserializes a value from pairs that are already percent-decoded.
+///
+/// The pairs are grouped and deserialized exactly like those of a parsed
+/// form, without encoding them again. Keys and values can be anything that
+/// converts into a `Cow<str>`, like `&str` or `String`.
+///
+/// ```
+/// use std::collections::HashMap;
+///
+/// use serde::Deserialize;
+///
+/// #[derive(Debug, PartialEq, Deserialize)]
+/// struct Filter {
+///     status: String,
+///     tag: Vec<String>,
+/// }
+///
+/// let pairs = vec![("status", "open"), ("tag", "bug"), ("tag", "ui")];
+/// let filter: Filter = serde_html_form::de::from_pairs(pairs).unwrap();
+/// assert_eq!(filter.tag, ["bug", "ui"]);
+///
+/// let mut multimap = HashMap::new();
+/// multimap.insert("status".to_owned(), vec!["closed".to_owned()]);
+/// multimap.insert("tag".to_owned(), vec!["docs".to_owned()]);
+/// let pairs = multimap.iter().flat_map(|(k, vs)| vs.iter().map(move |v| (k, v)));
+/// let filter: Filter = serde_html_form::de::from_pairs(pairs).unwrap();
+/// assert_eq!(filter, Filter { status: "closed".into(), tag: vec!["docs".into()] });
+/// ```
+pub fn from_pairs<'de, T, I, K, V>(pairs: I) -> Result<T, Error>
+where
+    T: Deserialize<'de>,
+    I: IntoIterator<Item = (K, V)>,
+    K: Into<Cow<'de, str>>,
+    V: Into<Cow<'de, str>>,
+{
+    T::deserialize(Deserializer::from_pairs(pairs))
+}
+
+/// Deserializes a `application/x-www-form-urlencoded` value from a `&str`,
+/// collecting an error for every field that fails instead of stopping at the
+/// first one.
+///
+/// See [`Deserializer::collect_errors`].
+///
+/// ```
+/// use serde::Deserialize;
+///
+/// #[derive(Debug, Deserialize)]
+/// struct Form {
+///     name: String,
+///     age: u8,
+///     height: f32,
+/// }
+///
+/// let errors =
+///     serde_html_form::de::from_str_collect_errors::<Form>("name=Jane&age=-1&height=tall")
+///         .unwrap_err();
+/// assert_eq!(
+///     errors.to_string(),
+///     "age: invalid digit found in string; height: invalid float literal"
+/// );
+/// assert_eq!(errors.value("height"), Some("tall"));
+/// ```
+pub fn from_str_collect_errors<'de, T>(input: &'de str) -> Result<T, FormErrors>
+where
+    T: Deserialize<'de>,
+{
+    Deserializer::with_input(Input::Str(input)).collect_errors()
+}
+
+/// Deserializes a `application/x-www-form-urlencoded` value from an
+/// [`io::Read`][std::io::Read].
+///
+/// The input is read in chunks and decoded with a [`Parser`], so the raw
+/// input is never buffered in full.
+///
+/// ```
+/// let meal = vec![
+///     ("bread".to_owned(), "baguette".to_owned()),
+///     ("cheese".to_owned(), "comté".to_owned()),
+/// ];
+///
+/// let input: &[u8] = b"bread=baguette&cheese=comt%C3%A9";
+/// assert_eq!(serde_html_form::de::from_reader::<_, Vec<(String, String)>>(input).unwrap(), meal);
+/// ```
+#[cfg(feature = "std")]
+pub fn from_reader<R, T>(mut reader: R) -> Result<T, Error>
+where
+    R: std::io::Read,
+    T: de::DeserializeOwned,
+{
+    use std::io::ErrorKind;
+
+    let mut parser = Parser::new();
+    let mut buf = [0; 8 * 1024];
+    loop {
+        match reader.read(&mut buf) {
+            Ok(0) => break,
+            Ok(n) => parser.feed(&buf[..n])?,
+            Err(e) if e.kind() == ErrorKind::Interrupted => {}
+            Err(e) => return Err(de::Error::custom(e)),
+        }
+    }
+
+    T::deserialize(parser.finish())
 }
 
 /// A deserializer for the `application/x-www-form-urlencoded` format.
@@ -74,19 +220,369 @@ where
 ///
 /// * Everything else but `deserialize_seq` and `deserialize_seq_fixed_size`
 ///   defers to `deserialize`.
+///
+/// * Keys are flat by default. Use [`key_notation`][Self::key_notation] to
+///   deserialize nested structs and maps.
 pub struct Deserializer<'de> {
-    inner: UrlEncodedParse<'de>,
+    inner: Input<'de>,
+    key_notation: KeyNotation,
+    duplicate_keys: DuplicateKeys,
+    infer_scalars: bool,
+    limits: Limits,
+    #[cfg(feature = "encoding")]
+    encoding: Option<&'static encoding_rs::Encoding>,
+    #[cfg(feature = "encoding")]
+    detect_charset: bool,
+    strict: bool,
+}
+
+/// How to handle a key that occurs more than once, for a field that is not a
+/// sequence.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum DuplicateKeys {
+    /// Return an error naming the key.
+    #[default]
+    Error,
+
+    /// Use the first value, ignore the others.
+    First,
+
+    /// Use the last value, ignore the others.
+    ///
+    /// This is what browsers and most server frameworks do, and allows the
+    /// pattern of a hidden input with a fallback value followed by a checkbox
+    /// of the same name.
+    Last,
 }
 
 impl<'de> Deserializer<'de> {
     /// Returns a new `Deserializer`.
     pub fn new(parse: UrlEncodedParse<'de>) -> Self {
-        Deserializer { inner: parse }
+        Self::with_input(Input::Parse(parse))
+    }
+
+    fn with_input(inner: Input<'de>) -> Self {
+        Deserializer {
+            inner,
+            key_notation: KeyNotation::Flat,
+            duplicate_keys: DuplicateKeys::Error,
+            infer_scalars: false,
+            limits: Limits::new(),
+            #[cfg(feature = "encoding")]
+            encoding: None,
+            #[cfg(feature = "encoding")]
+            detect_charset: false,
+            strict: false,
+        }
     }
 
     /// Returns a new `Deserializer` from a `&[u8]`.
     pub fn from_bytes(input: &'de [u8]) -> Self {
-        Self::new(parse(input))
+        Self::with_input(Input::Bytes(input))
+    }
+
+    /// Returns a new `Deserializer` from pairs that are already
+    /// percent-decoded.
+    ///
+    /// The pairs are collected into a `Vec` right away, so the iterator does
+    /// not have to outlive this call. [`Limits`] are only checked once
+    /// deserialization starts, so they do not bound the memory used for
+    /// collecting the pairs.
+    ///
+    /// See [`from_pairs`].
+    pub fn from_pairs<I, K, V>(pairs: I) -> Self
+    where
+        I: IntoIterator<Item = (K, V)>,
+        K: Into<Cow<'de, str>>,
+        V: Into<Cow<'de, str>>,
+    {
+        let pairs: Vec<_> =
+            pairs.into_iter().map(|(k, v)| (k.into(), Part::new(v.into()))).collect();
+        Self::with_input(Input::Pairs(pairs.into_iter()))
+    }
+
+    /// Sets the notation used to interpret keys as paths into nested structs
+    /// and maps.
+    ///
+    /// This only affects deserialization of structs and maps, not sequences
+    /// of pairs like `Vec<(String, String)>`.
+    ///
+    /// ```
+    /// # use std::collections::HashMap;
+    /// use serde::Deserialize;
+    /// use serde_html_form::{Deserializer, KeyNotation};
+    ///
+    /// #[derive(Debug, PartialEq, Deserialize)]
+    /// struct Form {
+    ///     user: User,
+    ///     filters: HashMap<String, String>,
+    /// }
+    ///
+    /// #[derive(Debug, PartialEq, Deserialize)]
+    /// struct User {
+    ///     name: String,
+    ///     email: String,
+    /// }
+    ///
+    /// let input = b"user[name]=Jane&user[email]=jane%40example.com&filters[status]=open";
+    /// let deserializer = Deserializer::from_bytes(input).key_notation(KeyNotation::Brackets);
+    /// let form = Form::deserialize(deserializer).unwrap();
+    ///
+    /// assert_eq!(form.user.email, "jane@example.com");
+    /// assert_eq!(form.filters["status"], "open");
+    /// ```
+    pub fn key_notation(mut self, notation: KeyNotation) -> Self {
+        self.key_notation = notation;
+        self
+    }
+
+    /// Sets how to handle keys that occur more than once for fields that are
+    /// not sequences.
+    ///
+    /// ```
+    /// use serde::Deserialize;
+    /// use serde_html_form::{de::DuplicateKeys, Deserializer};
+    ///
+    /// #[derive(Debug, PartialEq, Deserialize)]
+    /// struct Form {
+    ///     subscribe: bool,
+    /// }
+    ///
+    /// let input = b"subscribe=false&subscribe=true";
+    /// assert_eq!(
+    ///     Form::deserialize(Deserializer::from_bytes(input)).unwrap_err().to_string(),
+    ///     "duplicate key `subscribe`"
+    /// );
+    ///
+    /// let deserializer = Deserializer::from_bytes(input).duplicate_keys(DuplicateKeys::Last);
+    /// assert_eq!(Form::deserialize(deserializer), Ok(Form { subscribe: true }));
+    /// ```
+    pub fn duplicate_keys(mut self, duplicate_keys: DuplicateKeys) -> Self {
+        self.duplicate_keys = duplicate_keys;
+        self
+    }
+
+    /// Sets whether to infer booleans and numbers for types that do not say
+    /// what they expect.
+    ///
+    /// Form values are strings, and by default they are only parsed as
+    /// booleans or numbers when the type asks for one. Serde buffers the input
+    /// for `#[serde(flatten)]`, `#[serde(untagged)]` and `#[serde(tag = "..")]`
+    /// without knowing the types of the fields, so fields like `u32` then fail
+    /// with "invalid type: string". With inference, `true`, `false` and values
+    /// that look like JSON numbers are passed on as booleans and numbers.
+    ///
+    /// In turn, string fields in such types fail for values that look like
+    /// numbers. Numbers with leading zeros like `007` are not inferred.
+    ///
+    /// ```
+    /// use serde::Deserialize;
+    /// use serde_html_form::Deserializer;
+    ///
+    /// #[derive(Debug, PartialEq, Deserialize)]
+    /// struct Search {
+    ///     q: String,
+    ///     #[serde(flatten)]
+    ///     page: Page,
+    /// }
+    ///
+    /// #[derive(Debug, PartialEq, Deserialize)]
+    /// struct Page {
+    ///     page: u32,
+    ///     per_page: u32,
+    /// }
+    ///
+    /// let input = b"q=pens&page=2&per_page=50";
+    /// assert!(Search::deserialize(Deserializer::from_bytes(input)).is_err());
+    ///
+    /// let deserializer = Deserializer::from_bytes(input).infer_scalars(true);
+    /// let search = Search::deserialize(deserializer).unwrap();
+    /// assert_eq!(search.page, Page { page: 2, per_page: 50 });
+    /// ```
+    pub fn infer_scalars(mut self, infer_scalars: bool) -> Self {
+        self.infer_scalars = infer_scalars;
+        self
+    }
+
+    /// Sets the [`Limits`] for deserializing untrusted input.
+    pub fn limits(mut self, limits: Limits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    /// Sets whether to reject malformed input instead of decoding it
+    /// leniently.
+    ///
+    /// In strict mode, empty keys, percent-escapes that are not followed by
+    /// two hex digits and invalid UTF-8 are errors that name the byte offset
+    /// of the problem in the input. By default, malformed escapes are kept as
+    /// they are and invalid UTF-8 is replaced with U+FFFD.
+    ///
+    /// This only applies to deserializers created with
+    /// [`from_bytes`][Self::from_bytes]. Use [`Parser::strict`] for input that
+    /// arrives in chunks.
+    ///
+    /// ```
+    /// use serde::Deserialize;
+    /// use serde_html_form::Deserializer;
+    ///
+    /// #[derive(Debug, PartialEq, Deserialize)]
+    /// struct Form {
+    ///     name: String,
+    /// }
+    ///
+    /// let input = b"name=Ren%E9e";
+    /// let form = Form::deserialize(Deserializer::from_bytes(input)).unwrap();
+    /// assert_eq!(form.name, "Ren\u{fffd}e");
+    ///
+    /// let error = Form::deserialize(Deserializer::from_bytes(input).strict(true)).unwrap_err();
+    /// assert_eq!(error.to_string(), "invalid UTF-8 at byte 8");
+    /// ```
+    pub fn strict(mut self, strict: bool) -> Self {
+        self.strict = strict;
+        self
+    }
+
+    /// Sets the encoding of the percent-decoded bytes, UTF-8 by default.
+    ///
+    /// This only applies to deserializers created with
+    /// [`from_bytes`][Self::from_bytes]. Bytes that are invalid in the
+    /// encoding are replaced with U+FFFD.
+    ///
+    /// ```
+    /// use serde::Deserialize;
+    /// use serde_html_form::Deserializer;
+    ///
+    /// #[derive(Debug, PartialEq, Deserialize)]
+    /// struct Form {
+    ///     name: String,
+    /// }
+    ///
+    /// let deserializer =
+    ///     Deserializer::from_bytes(b"name=Ren%E9e").encoding(encoding_rs::WINDOWS_1252);
+    /// assert_eq!(Form::deserialize(deserializer), Ok(Form { name: "Renée".to_owned() }));
+    /// ```
+    #[cfg(feature = "encoding")]
+    pub fn encoding(mut self, encoding: &'static encoding_rs::Encoding) -> Self {
+        self.encoding = Some(encoding);
+        self
+    }
+
+    /// Sets whether to use the encoding named by the `_charset_` field of the
+    /// form, if there is one with a known label.
+    ///
+    /// Browsers fill a hidden input named `_charset_` with the encoding they
+    /// used to submit the form. If the field is missing or its value is not
+    /// a known label, the [`encoding`][Self::encoding] is used.
+    ///
+    /// ```
+    /// use serde::Deserialize;
+    /// use serde_html_form::Deserializer;
+    ///
+    /// #[derive(Debug, PartialEq, Deserialize)]
+    /// struct Form {
+    ///     name: String,
+    /// }
+    ///
+    /// let input = b"_charset_=Shift_JIS&name=%93%FA%96%7B";
+    /// let deserializer = Deserializer::from_bytes(input).detect_charset(true);
+    /// assert_eq!(Form::deserialize(deserializer), Ok(Form { name: "日本".to_owned() }));
+    /// ```
+    #[cfg(feature = "encoding")]
+    pub fn detect_charset(mut self, detect_charset: bool) -> Self {
+        self.detect_charset = detect_charset;
+        self
+    }
+
+    /// Deserializes a `T`, collecting an error for every field that fails
+    /// instead of stopping at the first one.
+    ///
+    /// When the value of a key can not be deserialized, the error is recorded
+    /// along with the submitted values of the key, and a default value like
+    /// zero or an empty string stands in for it, so the input is only
+    /// deserialized once. Every missing top-level field is reported too. A
+    /// missing field of a nested struct fails the value of the struct as a
+    /// whole. The returned [`FormErrors`] also contain all submitted values,
+    /// for rendering the form again with what the user entered.
+    ///
+    /// ```
+    /// use serde::Deserialize;
+    /// use serde_html_form::Deserializer;
+    ///
+    /// #[derive(Debug, Deserialize)]
+    /// struct Form {
+    ///     email: String,
+    ///     age: u8,
+    /// }
+    ///
+    /// let errors = Deserializer::from_bytes(b"age=old").collect_errors::<Form>().unwrap_err();
+    /// let messages: Vec<_> = errors.iter().map(|e| e.to_string()).collect();
+    /// assert_eq!(messages, ["age: invalid digit found in string", "email: missing field `email`"]);
+    /// assert_eq!(errors.get("age").unwrap().values(), ["old"]);
+    /// ```
+    pub fn collect_errors<T>(self) -> Result<T, FormErrors>
+    where
+        T: Deserialize<'de>,
+    {
+        form_errors::collect_errors(self)
+    }
+
+    /// Returns the pairs of string input if they need no checks, so that
+    /// they can be iterated directly instead of through `checked_pairs`.
+    fn str_pairs(&self) -> Option<StrPairs<'de>> {
+        #[cfg(feature = "encoding")]
+        if self.encoding.is_some() || self.detect_charset {
+            return None;
+        }
+
+        match self.inner {
+            Input::Str(input) if !self.strict && !self.limits.checks_pairs() => {
+                Some(StrPairs::new(input))
+            }
+            _ => None,
+        }
+    }
+
+    /// Deserializes a map, or a struct with the given fields, with flat keys,
+    /// see `visit_flat`.
+    fn deserialize_flat<V>(
+        self,
+        fields: Option<&'static [&'static str]>,
+        visitor: V,
+    ) -> Result<V::Value, Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        let Deserializer { duplicate_keys, infer_scalars, limits, .. } = self;
+        match self.str_pairs() {
+            Some(pairs) => {
+                let unique =
+                    has_unique_keys(pairs.remaining().as_bytes(), fields.unwrap_or_default());
+                let pairs = pairs.map(Ok);
+                visit_flat(pairs, unique, fields, duplicate_keys, infer_scalars, &limits, visitor)
+            }
+            None => {
+                let pairs = self.checked_pairs();
+                let unique = pairs.get_ref().has_unique_keys(fields.unwrap_or_default());
+                visit_flat(pairs, unique, fields, duplicate_keys, infer_scalars, &limits, visitor)
+            }
+        }
+    }
+
+    fn checked_pairs(self) -> Checked<Input<'de>> {
+        #[cfg(feature = "encoding")]
+        let inner =
+            charset::decode_input(self.inner, self.encoding, self.detect_charset, self.strict);
+        #[cfg(not(feature = "encoding"))]
+        let inner = self.inner;
+
+        let inner = match inner {
+            Input::Bytes(bytes) if self.strict => Input::Raw(RawPairs::new(bytes).strict(true)),
+            Input::Str(s) if self.strict => Input::Raw(RawPairs::new(s.as_bytes()).strict(true)),
+            inner => inner,
+        };
+        Checked::new(inner, self.limits)
     }
 }
 
@@ -104,22 +600,67 @@ impl<'de> de::Deserializer<'de> for Deserializer<'de> {
     where
         V: de::Visitor<'de>,
     {
-        visitor.visit_map(MapDeserializer::new(group_entries(self.inner).into_iter()))
+        let Deserializer { key_notation, duplicate_keys, infer_scalars, limits, .. } = self;
+        match key_notation {
+            KeyNotation::Flat => self.deserialize_flat(None, visitor),
+            notation => {
+                let pairs = self.checked_pairs();
+                let entries =
+                    nested::group_nested(pairs, notation, duplicate_keys, infer_scalars, &limits)?;
+                visitor.visit_map(Entries::new(entries.into_iter()))
+            }
+        }
+    }
+
+    fn deserialize_struct<V>(
+        self,
+        _name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        match self.key_notation {
+            KeyNotation::Flat => self.deserialize_flat(Some(fields), visitor),
+            _ => self.deserialize_map(visitor),
+        }
     }
 
     fn deserialize_seq<V>(self, visitor: V) -> Result<V::Value, Self::Error>
     where
         V: de::Visitor<'de>,
     {
-        visitor.visit_seq(MapDeserializer::new(PartIterator(self.inner)))
+        if let Some(pairs) = self.str_pairs() {
+            let pairs = pairs.map(|(key, value)| (Part::new(key), value));
+            return visitor.visit_seq(MapDeserializer::new(pairs));
+        }
+
+        let mut error = None;
+        let result = visitor.visit_seq(MapDeserializer::new(PartIterator {
+            pairs: self.checked_pairs(),
+            error: &mut error,
+        }));
+
+        match error {
+            Some(e) => Err(e),
+            None => result,
+        }
     }
 
     fn deserialize_unit<V>(self, visitor: V) -> Result<V::Value, Self::Error>
     where
         V: de::Visitor<'de>,
     {
-        let deserializer = MapDeserializer::new(PartIterator(self.inner));
-        deserializer.end()?;
+        let mut error = None;
+        let deserializer =
+            MapDeserializer::new(PartIterator { pairs: self.checked_pairs(), error: &mut error });
+        let result = deserializer.end();
+
+        if let Some(e) = error {
+            return Err(e);
+        }
+        result?;
         visitor.visit_unit()
     }
 
@@ -150,7 +691,6 @@ impl<'de> de::Deserializer<'de> for Deserializer<'de> {
         byte_buf
         unit_struct
         tuple_struct
-        struct
         identifier
         tuple
         enum
@@ -164,6 +704,10 @@ impl<'de> de::Deserializer<'de> for Deserializer<'de> {
 /// other crates, as it may appear to work at first but result in strange
 /// behavior later.
 ///
+/// Inside a struct that is `#[serde(flatten)]`ed, [`Deserializer::infer_scalars`]
+/// has already read values like `true` and `1.50` as booleans and numbers. They
+/// are formatted again before `T` sees them, so `1.50` arrives as `1.5`.
+///
 /// # Example
 ///
 /// ```
@@ -184,43 +728,393 @@ where
     EmptyAsNone::deserialize(deserializer).map(|EmptyAsNone(option)| option)
 }
 
-struct PartIterator<'de>(UrlEncodedParse<'de>);
+/// Deserialization helper for HTML checkboxes.
+///
+/// A checked checkbox submits its `value` attribute, `on` by default. This
+/// helper accepts `on`, `1`, `true` and `yes` as `true`, and `off`, `0`,
+/// `false`, `no` and the empty string as `false`, ignoring ASCII case.
+///
+/// An unchecked checkbox is not submitted at all, so combine this with
+/// `#[serde(default)]` to get `false` for a missing key.
+///
+/// If the key occurs more than once, the last value is used. This supports
+/// the pattern of a hidden input with the value `0` followed by a checkbox
+/// with the value `1`, which makes sure the key is always submitted.
+///
+/// Use with `#[serde(deserialize_with)]`. Do not use with deserializers from
+/// other crates, as it may appear to work at first but result in strange
+/// behavior later.
+///
+/// Where [`Deserializer::infer_scalars`] reads the value as a boolean or a
+/// number, which happens with `#[serde(flatten)]`, `true`, `1` and `0` are
+/// still accepted.
+///
+/// # Example
+///
+/// ```
+/// # use serde::Deserialize;
+/// #[derive(Debug, PartialEq, Deserialize)]
+/// struct Form {
+///     #[serde(default, deserialize_with = "serde_html_form::de::checkbox")]
+///     agree: bool,
+/// }
+///
+/// assert_eq!(serde_html_form::from_str("agree=on"), Ok(Form { agree: true }));
+/// assert_eq!(serde_html_form::from_str(""), Ok(Form { agree: false }));
+/// assert_eq!(serde_html_form::from_str("agree=0&agree=1"), Ok(Form { agree: true }));
+/// assert_eq!(serde_html_form::from_str("agree=0"), Ok(Form { agree: false }));
+/// ```
+pub fn checkbox<'de, D>(deserializer: D) -> Result<bool, D::Error>
+where
+    D: de::Deserializer<'de>,
+{
+    deserializer.deserialize_any(CheckboxVisitor)
+}
 
-impl<'de> Iterator for PartIterator<'de> {
-    type Item = (Part<'de>, Part<'de>);
+/// Deserialization helper that zips parallel repeated fields into a sequence
+/// of structs.
+///
+/// HTML tables of inputs often submit their rows as parallel repeated fields,
+/// like `name=a&name=b&age=1&age=2`. This helper turns the values of every
+/// field of `T` into one `T` per position, so the example results in
+/// `[{ name: a, age: 1 }, { name: b, age: 2 }]`. It is an error if the fields
+/// are repeated a different number of times.
+///
+/// Use with `#[serde(flatten, deserialize_with)]`. `T` has to be a struct
+/// without flattened fields of its own. Do not use with deserializers from
+/// other crates, as it may appear to work at first but result in strange
+/// behavior later.
+///
+/// Values that [`Deserializer::infer_scalars`] reads as numbers are written
+/// out again before they are deserialized into the fields of `T`, so a name of
+/// `1.50` becomes `1.5`.
+///
+/// # Example
+///
+/// ```
+/// # use serde::Deserialize;
+/// #[derive(Debug, PartialEq, Deserialize)]
+/// struct Form {
+///     csrf_token: String,
+///     #[serde(flatten, deserialize_with = "serde_html_form::de::rows")]
+///     people: Vec<Person>,
+/// }
+///
+/// #[derive(Debug, PartialEq, Deserialize)]
+/// struct Person {
+///     name: String,
+///     age: Option<u8>,
+/// }
+///
+/// let form: Form = serde_html_form::from_str("csrf_token=x&name=a&age=1&name=b&age=").unwrap();
+/// assert_eq!(
+///     form.people,
+///     [Person { name: "a".to_owned(), age: Some(1) }, Person { name: "b".to_owned(), age: None },]
+/// );
+/// ```
+pub fn rows<'de, T, D>(deserializer: D) -> Result<Vec<T>, D::Error>
+where
+    T: Deserialize<'de>,
+    D: de::Deserializer<'de>,
+{
+    let fields = struct_fields::<T>()
+        .ok_or_else(|| de::Error::custom("`rows` can only be used with structs"))?;
+    deserializer.deserialize_struct("Rows", fields, RowsVisitor { _phantom: PhantomData })
+}
+
+/// The source of the pairs of a `Deserializer`.
+enum Input<'de> {
+    /// Raw input that is split into pairs once iteration starts.
+    Bytes(&'de [u8]),
+    Raw(RawPairs<'de>),
+    /// Like `Bytes`, for input that is known to be valid UTF-8.
+    Str(&'de str),
+    StrPairs(StrPairs<'de>),
+    Parse(UrlEncodedParse<'de>),
+    Pairs(vec::IntoIter<(Cow<'de, str>, Part<'de>)>),
+    #[cfg(feature = "encoding")]
+    Decode(charset::Decode<'de>),
+    /// An error found before deserialization, like malformed input at the
+    /// end of a strict [`Parser`].
+    Failed(Option<Error>),
+}
 
+impl Input<'_> {
+    /// Whether no key occurs more than once in input that has not been split
+    /// into pairs yet, see `has_unique_keys`.
+    ///
+    /// This is `false` for other inputs, whose keys might not compare equal
+    /// before decoding when they do after.
+    fn has_unique_keys(&self, fields: &[&str]) -> bool {
+        match self {
+            Input::Bytes(input) => has_unique_keys(input, fields),
+            Input::Str(input) => has_unique_keys(input.as_bytes(), fields),
+            Input::Raw(pairs) => has_unique_keys(pairs.remaining(), fields),
+            _ => false,
+        }
+    }
+}
+
+impl<'de> Iterator for Input<'de> {
+    type Item = Result<(Cow<'de, str>, Part<'de>), Error>;
+
+    #[inline]
     fn next(&mut self) -> Option<Self::Item> {
-        self.0.next().map(|(k, v)| (Part(k), Part(v)))
+        match self {
+            Input::Bytes(bytes) => {
+                *self = Input::Raw(RawPairs::new(bytes));
+                self.next()
+            }
+            Input::Raw(pairs) => Some(pairs.next()?.map(|(key, value, span)| {
+                (Part::from_bytes(key).value, Part::from_bytes(value).with_span(span))
+            })),
+            Input::Str(s) => {
+                *self = Input::StrPairs(StrPairs::new(s));
+                self.next()
+            }
+            Input::StrPairs(pairs) => pairs.next().map(Ok),
+            Input::Parse(parse) => parse.next().map(|(k, v)| Ok((k, Part::new(v)))),
+            Input::Pairs(pairs) => pairs.next().map(Ok),
+            #[cfg(feature = "encoding")]
+            Input::Decode(pairs) => pairs.next(),
+            Input::Failed(error) => error.take().map(Err),
+        }
     }
 }
 
-#[cfg(feature = "std")]
-type RandomState = std::collections::hash_map::RandomState;
+/// An iterator over the pairs of a form that stops at the first pair that
+/// exceeds the limits, storing the error.
+struct PartIterator<'a, 'de> {
+    pairs: Checked<Input<'de>>,
+    error: &'a mut Option<Error>,
+}
 
-#[cfg(not(feature = "std"))]
-type RandomState = compile_error!("the `std` feature is currently required");
+impl<'de> Iterator for PartIterator<'_, 'de> {
+    type Item = (Part<'de>, Part<'de>);
 
-fn group_entries(
-    parse: UrlEncodedParse<'_>,
-) -> IndexMap<Part<'_>, ValOrVec<Part<'_>>, RandomState> {
-    use map::Entry::*;
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.error.is_some() {
+            return None;
+        }
 
-    let mut res = IndexMap::default();
+        match self.pairs.next()? {
+            Ok((k, v)) => Some((Part::new(k), v)),
+            Err(e) => {
+                *self.error = Some(e);
+                None
+            }
+        }
+    }
+}
+
+fn group_entries<'de>(
+    pairs: impl Iterator<Item = Result<(Cow<'de, str>, Part<'de>), Error>>,
+    limits: &Limits,
+) -> Result<KeyMap<Cow<'de, str>, ValOrVec<Part<'de>>>, Error> {
+    use key_map::Entry::*;
 
-    // silence unhelpful errors when we hit the compile_error! above anyways
-    #[cfg(feature = "std")]
-    for (key, value) in parse {
-        match res.entry(Part(key)) {
+    let mut res = KeyMap::default();
+
+    for pair in pairs {
+        let (key, value) = pair?;
+        match res.entry(key) {
             Vacant(v) => {
-                v.insert(ValOrVec::Val(Part(value)));
+                v.insert(ValOrVec::Val(value));
             }
             Occupied(mut o) => {
-                o.get_mut().push(Part(value));
+                o.get_mut().push(value);
+                limits.check_values_per_key(o.key(), o.get().len())?;
             }
         }
     }
 
-    res
+    Ok(res)
+}
+
+/// Deserializes a map, or a struct with the given fields, from pairs with
+/// flat keys.
+///
+/// If no key repeats, see `has_unique_keys`, the pairs are passed on to the
+/// visitor as they are parsed. Otherwise, the values of each key are grouped first, without
+/// hashing the keys of struct fields, see `group_fields`.
+fn visit_flat<'de, I, V>(
+    pairs: I,
+    unique: bool,
+    fields: Option<&'static [&'static str]>,
+    duplicate_keys: DuplicateKeys,
+    infer_scalars: bool,
+    limits: &Limits,
+    visitor: V,
+) -> Result<V::Value, Error>
+where
+    I: Iterator<Item = Result<(Cow<'de, str>, Part<'de>), Error>>,
+    V: de::Visitor<'de>,
+{
+    if unique {
+        return visitor.visit_map(UniqueEntries::new(pairs, duplicate_keys, infer_scalars));
+    }
+
+    let into_entry = |(key, values): (Cow<'de, str>, _)| {
+        let values = Values::grouped(&key, values, duplicate_keys).infer_scalars(infer_scalars);
+        (Part::new(key), values)
+    };
+    match fields {
+        Some(fields) => {
+            let entries = group_fields(pairs, fields, limits)?;
+            visitor.visit_map(Entries::new(entries.into_iter().map(into_entry)))
+        }
+        None => {
+            let entries = group_entries(pairs, limits)?;
+            visitor.visit_map(Entries::new(entries.into_iter().map(into_entry)))
+        }
+    }
+}
+
+/// Whether no key of `input` occurs more than once, so that its pairs can be
+/// passed on without grouping them.
+///
+/// Keys are compared before percent-decoding, so this is `false` for keys
+/// with escapes or bytes that are not ASCII, which might decode to the same
+/// string. Keys that are fields are tracked by their index. Other keys are
+/// only compared with the ones before them if a cheap hash matches, and there
+/// may be up to `MAX_LINEAR_KEYS` of them.
+fn has_unique_keys(input: &[u8], fields: &[&str]) -> bool {
+    // With more pairs than that, some key repeats or there are too many
+    // other keys, which is cheaper to find out by counting them first.
+    let max_keys = fields.len().min(64) + MAX_LINEAR_KEYS;
+    if input.iter().filter(|&&b| b == b'&').count() >= max_keys {
+        return false;
+    }
+
+    let mut seen_fields = 0_u64;
+    let mut other_hashes = [0_u32; MAX_LINEAR_KEYS];
+    let mut other_keys = 0;
+
+    let mut rest = input;
+    while let Some(&first) = rest.first() {
+        if first == b'&' {
+            rest = &rest[1..];
+            continue;
+        }
+
+        // The key ends at the first `=` or `&`, and is compared as it is if
+        // it has no escapes or bytes that are not ASCII.
+        let end = rest
+            .iter()
+            .position(|&b| matches!(b, b'=' | b'&' | b'%' | b'+') || !b.is_ascii())
+            .unwrap_or(rest.len());
+        let key = &rest[..end];
+        rest = match rest.get(end) {
+            Some(b'=') => match rest[end..].iter().position(|&b| b == b'&') {
+                Some(pos) => &rest[end + pos..],
+                None => &[],
+            },
+            Some(b'&') | None => &rest[end..],
+            Some(_) => return false,
+        };
+
+        match fields.iter().position(|field| field.as_bytes() == key) {
+            Some(i) if i < 64 => {
+                if seen_fields & (1 << i) != 0 {
+                    return false;
+                }
+                seen_fields |= 1 << i;
+            }
+            _ => {
+                if other_keys == MAX_LINEAR_KEYS {
+                    return false;
+                }
+
+                let hash =
+                    key.iter().fold(key.len() as u32, |h, &b| h.wrapping_mul(31) ^ u32::from(b));
+                let before = &input[..input.len() - rest.len()];
+                if other_hashes[..other_keys].contains(&hash)
+                    && raw_keys(before).rev().skip(1).any(|k| k == key)
+                {
+                    return false;
+                }
+                other_hashes[other_keys] = hash;
+                other_keys += 1;
+            }
+        }
+    }
+
+    true
+}
+
+/// Returns the keys of the pairs of `input`, before percent-decoding.
+fn raw_keys(input: &[u8]) -> impl DoubleEndedIterator<Item = &[u8]> {
+    input.split(|&b| b == b'&').filter(|sequence| !sequence.is_empty()).map(|sequence| {
+        match sequence.iter().position(|&b| b == b'=') {
+            Some(pos) => &sequence[..pos],
+            None => sequence,
+        }
+    })
+}
+
+/// A key with its values, grouped by `group_fields`.
+type Group<'de> = (Cow<'de, str>, ValOrVec<Part<'de>>);
+
+/// The number of different keys up to which keys are compared one by one
+/// instead of building a map.
+const MAX_LINEAR_KEYS: usize = 32;
+
+/// Groups the values of repeated keys like `group_entries`, in the order of
+/// their first occurrence, for deserializing a struct with the given fields.
+///
+/// Keys that are fields are found by comparing them with `fields`, and other
+/// keys, which are usually ignored, by comparing them with the keys so far.
+/// This is faster than hashing them for forms of usual size, so a map is only
+/// built for forms with many different keys.
+fn group_fields<'de>(
+    pairs: impl Iterator<Item = Result<(Cow<'de, str>, Part<'de>), Error>>,
+    fields: &'static [&'static str],
+    limits: &Limits,
+) -> Result<Vec<Group<'de>>, Error> {
+    // The index into `entries` of each field, on the stack for most structs.
+    let mut inline_slots = [None; 16];
+    let mut heap_slots = Vec::new();
+    let field_slots = match inline_slots.get_mut(..fields.len()) {
+        Some(slots) => slots,
+        None => {
+            heap_slots.resize(fields.len(), None);
+            &mut heap_slots[..]
+        }
+    };
+    let mut other_slots: Option<KeyMap<Cow<'de, str>, usize>> = None;
+    let mut entries: Vec<Group<'de>> = Vec::new();
+
+    for pair in pairs {
+        let (key, value) = pair?;
+        let field = fields.iter().position(|&field| field == key);
+        let index = match (field, &other_slots) {
+            (Some(i), _) => field_slots[i],
+            (None, Some(map)) => map.get(&*key).copied(),
+            (None, None) => entries.iter().position(|(entry_key, _)| *entry_key == key),
+        };
+
+        if let Some((entry_key, values)) = index.and_then(|i| entries.get_mut(i)) {
+            values.push(value);
+            limits.check_values_per_key(entry_key, values.len())?;
+            continue;
+        }
+
+        match (field, &mut other_slots) {
+            (Some(i), _) => field_slots[i] = Some(entries.len()),
+            (None, Some(map)) => {
+                map.insert(key.clone(), entries.len());
+            }
+            (None, None) => {}
+        }
+        entries.push((key, ValOrVec::Val(value)));
+
+        if other_slots.is_none() && entries.len() > MAX_LINEAR_KEYS {
+            let keys = entries.iter().enumerate().map(|(i, (key, _))| (key.clone(), i));
+            other_slots = Some(keys.collect());
+        }
+    }
+
+    Ok(entries)
 }
 
 #[cfg(test)]
@@ -0,0 +1,72 @@
+use alloc::{borrow::Cow, string::String};
+
+use encoding_rs::{Encoding, UTF_8};
+
+use super::{part::Part, raw::RawPairs, Error, Input};
+
+/// The name of the field that browsers fill with the encoding of a form.
+const CHARSET_FIELD: &[u8] = b"_charset_";
+
+/// Prepares the input of a `Deserializer` for decoding with the given
+/// encoding, or the one named by the `_charset_` field.
+pub(super) fn decode_input<'de>(
+    input: Input<'de>,
+    encoding: Option<&'static Encoding>,
+    detect_charset: bool,
+    strict: bool,
+) -> Input<'de> {
+    let bytes = match input {
+        Input::Bytes(bytes) => bytes,
+        Input::Str(s) => s.as_bytes(),
+        _ => return input,
+    };
+
+    let detected = if detect_charset { detect(bytes) } else { None };
+    let encoding = detected.or(encoding).unwrap_or(UTF_8).output_encoding();
+    if encoding == UTF_8 {
+        input
+    } else {
+        let pairs = RawPairs::new(bytes).strict(strict).check_utf8(false);
+        Input::Decode(Decode { pairs, encoding })
+    }
+}
+
+/// Returns the encoding named by the first `_charset_` field, if any.
+fn detect(input: &[u8]) -> Option<&'static Encoding> {
+    RawPairs::new(input)
+        .filter_map(Result::ok)
+        .find(|(key, ..)| *key == CHARSET_FIELD)
+        .and_then(|(_, value, _)| Encoding::for_label(&value))
+}
+
+/// An iterator over the pairs of a form in a legacy encoding.
+pub(super) struct Decode<'de> {
+    pairs: RawPairs<'de>,
+    encoding: &'static Encoding,
+}
+
+impl<'de> Iterator for Decode<'de> {
+    type Item = Result<(Cow<'de, str>, Part<'de>), Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let encoding = self.encoding;
+        let pair = self.pairs.next()?;
+        Some(pair.map(|(key, value, span)| {
+            (decode(key, encoding).value, decode(value, encoding).with_span(span))
+        }))
+    }
+}
+
+/// Decodes the bytes, keeping them unless they decode to themselves.
+fn decode<'de>(bytes: Cow<'de, [u8]>, encoding: &'static Encoding) -> Part<'de> {
+    match bytes {
+        Cow::Borrowed(bytes) => match encoding.decode_without_bom_handling(bytes).0 {
+            Cow::Borrowed(s) => Part::new(Cow::Borrowed(s)),
+            Cow::Owned(s) => Part::with_raw(Cow::Owned(s), Cow::Borrowed(bytes)),
+        },
+        Cow::Owned(bytes) => match encoding.decode_without_bom_handling(&bytes).0 {
+            Cow::Borrowed(s) => Part::new(Cow::Owned(String::from(s))),
+            Cow::Owned(s) => Part::with_raw(Cow::Owned(s), Cow::Owned(bytes)),
+        },
+    }
+}
@@ -0,0 +1,85 @@
+use core::fmt;
+
+use serde_core::de::{self, Unexpected, Visitor};
+
+pub(super) struct CheckboxVisitor;
+
+impl CheckboxVisitor {
+    fn parse<E>(self, v: &str) -> Result<bool, E>
+    where
+        E: de::Error,
+    {
+        const TRUE: &[&str] = &["on", "1", "true", "yes"];
+        const FALSE: &[&str] = &["", "off", "0", "false", "no"];
+
+        if TRUE.iter().any(|t| t.eq_ignore_ascii_case(v)) {
+            Ok(true)
+        } else if FALSE.iter().any(|f| f.eq_ignore_ascii_case(v)) {
+            Ok(false)
+        } else {
+            Err(de::Error::invalid_value(Unexpected::Str(v), &self))
+        }
+    }
+}
+
+impl<'de> Visitor<'de> for CheckboxVisitor {
+    type Value = bool;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        formatter.write_str("a checkbox value")
+    }
+
+    fn visit_bool<E>(self, v: bool) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        Ok(v)
+    }
+
+    fn visit_i64<E>(self, v: i64) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        self.parse(itoa::Buffer::new().format(v))
+    }
+
+    fn visit_u64<E>(self, v: u64) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        self.parse(itoa::Buffer::new().format(v))
+    }
+
+    fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        self.parse(v)
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+    where
+        A: de::SeqAccess<'de>,
+    {
+        // With a hidden input in front of the checkbox, the last value wins
+        let mut last = false;
+        while let Some(value) = seq.next_element_seed(CheckboxSeed)? {
+            last = value;
+        }
+
+        Ok(last)
+    }
+}
+
+struct CheckboxSeed;
+
+impl<'de> de::DeserializeSeed<'de> for CheckboxSeed {
+    type Value = bool;
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        deserializer.deserialize_any(CheckboxVisitor)
+    }
+}
@@ -0,0 +1,332 @@
+//! Wrappers around a deserializer that record the errors of values in maps
+//! and structs and deserialize placeholders instead, for
+//! `Deserializer::collect_errors`.
+//!
+//! Errors are recorded where a map or struct asks for a value, so they have
+//! the key and span that the `MapAccess` of the value adds. Errors about
+//! anything else, like the key itself, are returned as usual.
+
+use alloc::{string::String, vec::Vec};
+use core::{fmt, marker::PhantomData, mem};
+
+use serde_core::de::{self, DeserializeSeed, Visitor};
+
+use super::{placeholder::Placeholder, utils::non_static_type_id, Error};
+
+/// A deserializer that records the errors of values in maps and structs in
+/// `errors`.
+pub(super) struct Collect<'a, D> {
+    deserializer: D,
+    errors: &'a mut Vec<Error>,
+}
+
+impl<'a, D> Collect<'a, D> {
+    pub fn new(deserializer: D, errors: &'a mut Vec<Error>) -> Self {
+        Collect { deserializer, errors }
+    }
+}
+
+macro_rules! forward_to_deserializer {
+    ($($method:ident($($arg:ident: $ty:ty),*),)*) => {
+        $(
+            fn $method<V>(self, $($arg: $ty,)* visitor: V) -> Result<V::Value, Self::Error>
+            where
+                V: Visitor<'de>,
+            {
+                let visitor = CollectVisitor { visitor, errors: self.errors };
+                self.deserializer.$method($($arg,)* visitor)
+            }
+        )*
+    };
+}
+
+impl<'de, D> de::Deserializer<'de> for Collect<'_, D>
+where
+    D: de::Deserializer<'de>,
+{
+    type Error = D::Error;
+
+    forward_to_deserializer! {
+        deserialize_any(),
+        deserialize_bool(),
+        deserialize_i8(),
+        deserialize_i16(),
+        deserialize_i32(),
+        deserialize_i64(),
+        deserialize_i128(),
+        deserialize_u8(),
+        deserialize_u16(),
+        deserialize_u32(),
+        deserialize_u64(),
+        deserialize_u128(),
+        deserialize_f32(),
+        deserialize_f64(),
+        deserialize_char(),
+        deserialize_str(),
+        deserialize_string(),
+        deserialize_bytes(),
+        deserialize_byte_buf(),
+        deserialize_option(),
+        deserialize_unit(),
+        deserialize_unit_struct(name: &'static str),
+        deserialize_newtype_struct(name: &'static str),
+        deserialize_seq(),
+        deserialize_tuple(len: usize),
+        deserialize_tuple_struct(name: &'static str, len: usize),
+        deserialize_map(),
+        deserialize_struct(name: &'static str, fields: &'static [&'static str]),
+        deserialize_enum(name: &'static str, variants: &'static [&'static str]),
+        deserialize_identifier(),
+        deserialize_ignored_any(),
+    }
+
+    fn is_human_readable(&self) -> bool {
+        self.deserializer.is_human_readable()
+    }
+}
+
+/// A visitor that wraps the maps, sequences and nested deserializers it is
+/// given, so that errors deeper down are recorded too.
+struct CollectVisitor<'a, V> {
+    visitor: V,
+    errors: &'a mut Vec<Error>,
+}
+
+macro_rules! forward_to_visitor {
+    ($($method:ident($($arg:ident: $ty:ty)?),)*) => {
+        $(
+            fn $method<E>(self, $($arg: $ty)?) -> Result<Self::Value, E>
+            where
+                E: de::Error,
+            {
+                self.visitor.$method($($arg)?)
+            }
+        )*
+    };
+}
+
+impl<'de, V> Visitor<'de> for CollectVisitor<'_, V>
+where
+    V: Visitor<'de>,
+{
+    type Value = V::Value;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.visitor.expecting(formatter)
+    }
+
+    forward_to_visitor! {
+        visit_bool(v: bool),
+        visit_i8(v: i8),
+        visit_i16(v: i16),
+        visit_i32(v: i32),
+        visit_i64(v: i64),
+        visit_i128(v: i128),
+        visit_u8(v: u8),
+        visit_u16(v: u16),
+        visit_u32(v: u32),
+        visit_u64(v: u64),
+        visit_u128(v: u128),
+        visit_f32(v: f32),
+        visit_f64(v: f64),
+        visit_char(v: char),
+        visit_str(v: &str),
+        visit_borrowed_str(v: &'de str),
+        visit_string(v: String),
+        visit_bytes(v: &[u8]),
+        visit_borrowed_bytes(v: &'de [u8]),
+        visit_byte_buf(v: Vec<u8>),
+        visit_none(),
+        visit_unit(),
+    }
+
+    fn visit_some<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        self.visitor.visit_some(Collect::new(deserializer, self.errors))
+    }
+
+    fn visit_newtype_struct<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        self.visitor.visit_newtype_struct(Collect::new(deserializer, self.errors))
+    }
+
+    fn visit_seq<A>(self, seq: A) -> Result<Self::Value, A::Error>
+    where
+        A: de::SeqAccess<'de>,
+    {
+        self.visitor.visit_seq(CollectSeq { seq, errors: self.errors })
+    }
+
+    fn visit_map<A>(self, map: A) -> Result<Self::Value, A::Error>
+    where
+        A: de::MapAccess<'de>,
+    {
+        self.visitor.visit_map(CollectMap { map, errors: self.errors })
+    }
+
+    fn visit_enum<A>(self, data: A) -> Result<Self::Value, A::Error>
+    where
+        A: de::EnumAccess<'de>,
+    {
+        self.visitor.visit_enum(CollectEnum { data, errors: self.errors })
+    }
+}
+
+/// A seed that deserializes its value with `Collect`.
+struct CollectSeed<'a, S> {
+    seed: S,
+    errors: &'a mut Vec<Error>,
+}
+
+impl<'de, S> DeserializeSeed<'de> for CollectSeed<'_, S>
+where
+    S: DeserializeSeed<'de>,
+{
+    type Value = S::Value;
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        self.seed.deserialize(Collect::new(deserializer, self.errors))
+    }
+}
+
+/// A map that records the error of a value and deserializes a placeholder
+/// instead, so that the visitor goes on with the next entry.
+struct CollectMap<'a, A> {
+    map: A,
+    errors: &'a mut Vec<Error>,
+}
+
+impl<'de, A> de::MapAccess<'de> for CollectMap<'_, A>
+where
+    A: de::MapAccess<'de>,
+{
+    type Error = A::Error;
+
+    fn next_key_seed<K>(&mut self, seed: K) -> Result<Option<K::Value>, Self::Error>
+    where
+        K: DeserializeSeed<'de>,
+    {
+        self.map.next_key_seed(seed)
+    }
+
+    fn next_value_seed<S>(&mut self, seed: S) -> Result<S::Value, Self::Error>
+    where
+        S: DeserializeSeed<'de>,
+    {
+        let retry = copy_seed::<S>();
+        let result = self.map.next_value_seed(CollectSeed { seed, errors: &mut *self.errors });
+        match (result, retry) {
+            (Err(error), Some(seed)) => {
+                self.errors.push(Error::from_error(error));
+                seed.deserialize(Placeholder).map_err(Error::into_error)
+            }
+            (result, _) => result,
+        }
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        self.map.size_hint()
+    }
+}
+
+/// Returns another `S` if it is a `PhantomData`, which is the seed that
+/// `MapAccess::next_value` passes on, so that the value can be deserialized
+/// from a placeholder after an error.
+fn copy_seed<'de, S: DeserializeSeed<'de>>() -> Option<S> {
+    if non_static_type_id::<S>() == non_static_type_id::<PhantomData<S::Value>>() {
+        // SAFETY: `S` is `PhantomData<S::Value>`, which has no data whatever
+        // its lifetimes are.
+        Some(unsafe { mem::transmute_copy::<PhantomData<S::Value>, S>(&PhantomData) })
+    } else {
+        None
+    }
+}
+
+/// A sequence whose elements are deserialized with `Collect`.
+struct CollectSeq<'a, A> {
+    seq: A,
+    errors: &'a mut Vec<Error>,
+}
+
+impl<'de, A> de::SeqAccess<'de> for CollectSeq<'_, A>
+where
+    A: de::SeqAccess<'de>,
+{
+    type Error = A::Error;
+
+    fn next_element_seed<T>(&mut self, seed: T) -> Result<Option<T::Value>, Self::Error>
+    where
+        T: DeserializeSeed<'de>,
+    {
+        self.seq.next_element_seed(CollectSeed { seed, errors: &mut *self.errors })
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        self.seq.size_hint()
+    }
+}
+
+/// An enum whose variant is deserialized with `Collect`.
+struct CollectEnum<'a, A> {
+    data: A,
+    errors: &'a mut Vec<Error>,
+}
+
+impl<'a, 'de, A> de::EnumAccess<'de> for CollectEnum<'a, A>
+where
+    A: de::EnumAccess<'de>,
+{
+    type Error = A::Error;
+    type Variant = CollectEnum<'a, A::Variant>;
+
+    fn variant_seed<V>(self, seed: V) -> Result<(V::Value, Self::Variant), Self::Error>
+    where
+        V: DeserializeSeed<'de>,
+    {
+        let (value, data) = self.data.variant_seed(seed)?;
+        Ok((value, CollectEnum { data, errors: self.errors }))
+    }
+}
+
+impl<'de, A> de::VariantAccess<'de> for CollectEnum<'_, A>
+where
+    A: de::VariantAccess<'de>,
+{
+    type Error = A::Error;
+
+    fn unit_variant(self) -> Result<(), Self::Error> {
+        self.data.unit_variant()
+    }
+
+    fn newtype_variant_seed<T>(self, seed: T) -> Result<T::Value, Self::Error>
+    where
+        T: DeserializeSeed<'de>,
+    {
+        self.data.newtype_variant_seed(CollectSeed { seed, errors: self.errors })
+    }
+
+    fn tuple_variant<V>(self, len: usize, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.data.tuple_variant(len, CollectVisitor { visitor, errors: self.errors })
+    }
+
+    fn struct_variant<V>(
+        self,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.data.struct_variant(fields, CollectVisitor { visitor, errors: self.errors })
+    }
+}
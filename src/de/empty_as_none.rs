@@ -2,14 +2,14 @@
 
 use alloc::{
     borrow::{Cow, ToOwned as _},
-    string::String,
+    string::{String, ToString as _},
     vec::Vec,
 };
 use core::{fmt, marker::PhantomData};
 
 use serde_core::de::{self, Deserialize, Unexpected, Visitor};
 
-use super::Part;
+use super::{nested::Node, utils::non_static_type_id, val_or_vec::Values, Part};
 
 /// Deserialization helper that treats empty values in a sequence as `None`.
 ///
@@ -54,11 +54,18 @@ where
     where
         D: serde_core::Deserializer<'de>,
     {
-        let s = deserializer.deserialize_string(CowStrVisitor)?;
+        // `#[serde(flatten)]` reads values with `deserialize_any`, which with
+        // `Deserializer::infer_scalars` turns some of them into booleans and
+        // numbers, so those are accepted as well.
+        let s = if is_form_deserializer::<D>() {
+            deserializer.deserialize_string(CowStrVisitor)?
+        } else {
+            deserializer.deserialize_any(CowStrVisitor)?
+        };
         let v = if s.is_empty() {
             None
         } else {
-            let value = T::deserialize(Part(s)).map_err(de::Error::custom)?;
+            let value = T::deserialize(Part::new(s)).map_err(de::Error::custom)?;
             Some(value)
         };
 
@@ -66,7 +73,17 @@ where
     }
 }
 
-struct CowStrVisitor;
+/// Whether `D` is one of the deserializers for the values of a form, as
+/// opposed to the one used by `#[serde(flatten)]`.
+fn is_form_deserializer<'de, D: de::Deserializer<'de>>() -> bool {
+    let id = non_static_type_id::<D>();
+    id == non_static_type_id::<Values<'static>>()
+        || id == non_static_type_id::<Part<'static>>()
+        || id == non_static_type_id::<Node<'static>>()
+}
+
+/// Visits a string, or a boolean or number that is turned back into one.
+pub(super) struct CowStrVisitor;
 
 impl<'de> Visitor<'de> for CowStrVisitor {
     type Value = Cow<'de, str>;
@@ -125,6 +142,34 @@ impl<'de> Visitor<'de> for CowStrVisitor {
             Err(e) => Err(de::Error::invalid_value(Unexpected::Bytes(&e.into_bytes()), &self)),
         }
     }
+
+    fn visit_bool<E>(self, v: bool) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        Ok(Cow::Owned(v.to_string()))
+    }
+
+    fn visit_i64<E>(self, v: i64) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        Ok(Cow::Owned(v.to_string()))
+    }
+
+    fn visit_u64<E>(self, v: u64) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        Ok(Cow::Owned(v.to_string()))
+    }
+
+    fn visit_f64<E>(self, v: f64) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        Ok(Cow::Owned(v.to_string()))
+    }
 }
 
 struct EmptyAsNoneListVisitor<L> {
@@ -0,0 +1,108 @@
+use alloc::borrow::Cow;
+
+use serde_core::de::{self, IntoDeserializer};
+
+use super::{part::Part, val_or_vec::Values, DuplicateKeys, Error};
+
+/// A `MapAccess` over the entries of a form, grouped by key.
+///
+/// Unlike `MapDeserializer`, this adds the key to errors about its value.
+pub(super) struct Entries<'de, I, V> {
+    entries: I,
+    value: Option<(Cow<'de, str>, V)>,
+}
+
+impl<'de, I, V> Entries<'de, I, V> {
+    pub fn new(entries: I) -> Self {
+        Entries { entries, value: None }
+    }
+}
+
+impl<'de, I, V> de::MapAccess<'de> for Entries<'de, I, V>
+where
+    I: Iterator<Item = (Part<'de>, V)>,
+    V: IntoDeserializer<'de, Error>,
+{
+    type Error = Error;
+
+    fn next_key_seed<K>(&mut self, seed: K) -> Result<Option<K::Value>, Self::Error>
+    where
+        K: de::DeserializeSeed<'de>,
+    {
+        match self.entries.next() {
+            Some((key, value)) => {
+                self.value = Some((key.value.clone(), value));
+                seed.deserialize(key).map(Some)
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn next_value_seed<T>(&mut self, seed: T) -> Result<T::Value, Self::Error>
+    where
+        T: de::DeserializeSeed<'de>,
+    {
+        let (key, value) = self.value.take().expect("MapAccess::next_value called before next_key");
+        seed.deserialize(value.into_deserializer()).map_err(|e| e.with_key(&key))
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        match self.entries.size_hint() {
+            (lower, Some(upper)) if lower == upper => Some(upper),
+            _ => None,
+        }
+    }
+}
+
+/// A `MapAccess` over the pairs of a form in which no key repeats, passing
+/// each pair on as it is parsed.
+///
+/// Like `Entries`, this adds the key to errors about its value. Errors from
+/// the pairs themselves, like exceeded limits, end the map.
+pub(super) struct UniqueEntries<'de, I> {
+    pairs: I,
+    value: Option<(Cow<'de, str>, Part<'de>)>,
+    duplicate_keys: DuplicateKeys,
+    infer_scalars: bool,
+}
+
+impl<'de, I> UniqueEntries<'de, I> {
+    pub fn new(pairs: I, duplicate_keys: DuplicateKeys, infer_scalars: bool) -> Self {
+        UniqueEntries { pairs, value: None, duplicate_keys, infer_scalars }
+    }
+}
+
+impl<'de, I> de::MapAccess<'de> for UniqueEntries<'de, I>
+where
+    I: Iterator<Item = Result<(Cow<'de, str>, Part<'de>), Error>>,
+{
+    type Error = Error;
+
+    fn next_key_seed<K>(&mut self, seed: K) -> Result<Option<K::Value>, Self::Error>
+    where
+        K: de::DeserializeSeed<'de>,
+    {
+        match self.pairs.next() {
+            Some(Ok((key, value))) => {
+                let part = Part::new(key.clone());
+                self.value = Some((key, value));
+                seed.deserialize(part).map(Some)
+            }
+            Some(Err(e)) => Err(e),
+            None => Ok(None),
+        }
+    }
+
+    fn next_value_seed<T>(&mut self, seed: T) -> Result<T::Value, Self::Error>
+    where
+        T: de::DeserializeSeed<'de>,
+    {
+        let (key, value) = self.value.take().expect("MapAccess::next_value called before next_key");
+        if self.infer_scalars {
+            let values = Values::new(value, self.duplicate_keys).infer_scalars(true);
+            return seed.deserialize(values).map_err(|e| e.with_key(&key));
+        }
+        let span = value.span();
+        seed.deserialize(value).map_err(|e| e.with_key(&key).with_span(span))
+    }
+}
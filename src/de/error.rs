@@ -0,0 +1,253 @@
+use alloc::{
+    boxed::Box,
+    string::{String, ToString as _},
+};
+use core::{
+    any::TypeId,
+    fmt,
+    mem::{self, ManuallyDrop},
+    ops::Range,
+};
+#[cfg(feature = "std")]
+use std::error;
+
+use serde_core::de::{self, Expected, Unexpected};
+
+use super::utils::non_static_type_id;
+
+/// Errors returned during deserializing from
+/// `application/x-www-form-urlencoded`.
+///
+/// Besides the message, an error can carry the key of the form field it is
+/// about and the byte span of the pair in the input, for showing the error
+/// next to the right input of a form.
+///
+/// ```
+/// use serde::Deserialize;
+/// use serde_html_form::de::ErrorKind;
+///
+/// #[derive(Debug, Deserialize)]
+/// struct Form {
+///     name: String,
+///     price: u32,
+/// }
+///
+/// let error = serde_html_form::from_str::<Form>("name=Pen&price=abc").unwrap_err();
+/// assert_eq!(error.kind(), ErrorKind::Parse);
+/// assert_eq!(error.key(), Some("price"));
+/// assert_eq!(error.span(), Some(9..18));
+/// assert_eq!(error.to_string(), "invalid digit found in string");
+/// ```
+#[derive(Clone, PartialEq, Eq)]
+pub struct Error(Box<ErrorImpl>);
+
+// Boxed to keep `Result`s small on the hot paths of deserialization.
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct ErrorImpl {
+    kind: ErrorKind,
+    message: String,
+    key: Option<String>,
+    span: Option<Range<usize>>,
+}
+
+/// The category of a deserialization [`Error`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ErrorKind {
+    /// A value could not be parsed as the expected type, like `abc` for a
+    /// number or an unknown variant of an enum.
+    Parse,
+
+    /// A field of a struct is missing from the input.
+    MissingField,
+
+    /// A key has more than one value for a field that is not a sequence, or
+    /// a field occurs more than once.
+    Duplicate,
+
+    /// The shape of the input does not match the expected type, like a plain
+    /// value for a nested struct.
+    Unsupported,
+
+    /// The input exceeds one of the [`Limits`](super::Limits).
+    LimitExceeded,
+
+    /// The input is malformed, see [`Deserializer::strict`](super::Deserializer::strict).
+    Syntax,
+
+    /// Any other error, like a custom error from a `Deserialize`
+    /// implementation.
+    Custom,
+}
+
+impl Error {
+    pub(crate) fn new(kind: ErrorKind, message: impl fmt::Display) -> Self {
+        Error(Box::new(ErrorImpl { kind, message: message.to_string(), key: None, span: None }))
+    }
+
+    /// Sets the key of the error, unless it already has one.
+    pub(crate) fn with_key(mut self, key: &str) -> Self {
+        if self.0.key.is_none() {
+            self.0.key = Some(key.into());
+        }
+        self
+    }
+
+    /// Sets the span of the error, unless it already has one.
+    pub(crate) fn with_span(mut self, span: Option<Range<usize>>) -> Self {
+        if self.0.span.is_none() {
+            self.0.span = span;
+        }
+        self
+    }
+
+    /// Converts this error into the error type of another deserializer, like
+    /// the one `#[serde(flatten)]` uses.
+    ///
+    /// That is usually this type again, in which case the error is returned
+    /// unchanged instead of keeping only its message.
+    pub(crate) fn into_error<E: de::Error>(self) -> E {
+        if non_static_type_id::<E>() == TypeId::of::<Error>() {
+            let error = ManuallyDrop::new(self);
+            // SAFETY: `E` is `Error`, and `error` is not dropped.
+            unsafe { mem::transmute_copy::<Error, E>(&error) }
+        } else {
+            E::custom(self)
+        }
+    }
+
+    /// Converts an error of another deserializer into this type, the reverse
+    /// of `into_error`.
+    pub(crate) fn from_error<E: de::Error>(error: E) -> Self {
+        if non_static_type_id::<E>() == TypeId::of::<Error>() {
+            let error = ManuallyDrop::new(error);
+            // SAFETY: `E` is `Error`, and `error` is not dropped.
+            unsafe { mem::transmute_copy::<E, Error>(&error) }
+        } else {
+            de::Error::custom(error)
+        }
+    }
+
+    /// Returns the category of this error.
+    pub fn kind(&self) -> ErrorKind {
+        self.0.kind
+    }
+
+    /// Returns the key of the form field this error is about, if known.
+    ///
+    /// For nested keys, this is the full key as it appears in the input, like
+    /// `items[0][price]`. For missing fields, it is the name of the field.
+    pub fn key(&self) -> Option<&str> {
+        self.0.key.as_deref()
+    }
+
+    /// Returns the byte span of the pair this error is about in the input, if
+    /// known.
+    ///
+    /// Spans are only available for input given as bytes or a string, or fed
+    /// to a [`Parser`](super::Parser).
+    pub fn span(&self) -> Option<Range<usize>> {
+        self.0.span.clone()
+    }
+}
+
+impl fmt::Debug for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Error")
+            .field("kind", &self.0.kind)
+            .field("message", &self.0.message)
+            .field("key", &self.0.key)
+            .field("span", &self.0.span)
+            .finish()
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.message.fmt(f)
+    }
+}
+
+#[cfg(feature = "std")]
+impl error::Error for Error {}
+
+#[cfg(not(feature = "std"))]
+impl de::StdError for Error {}
+
+impl de::Error for Error {
+    fn custom<T: fmt::Display>(msg: T) -> Self {
+        Error::new(ErrorKind::Custom, msg)
+    }
+
+    fn invalid_type(unexp: Unexpected<'_>, exp: &dyn Expected) -> Self {
+        Error::new(
+            ErrorKind::Unsupported,
+            format_args!("invalid type: {}, expected {}", unexp, exp),
+        )
+    }
+
+    fn invalid_value(unexp: Unexpected<'_>, exp: &dyn Expected) -> Self {
+        Error::new(ErrorKind::Parse, format_args!("invalid value: {}, expected {}", unexp, exp))
+    }
+
+    fn invalid_length(len: usize, exp: &dyn Expected) -> Self {
+        Error::new(ErrorKind::Parse, format_args!("invalid length {}, expected {}", len, exp))
+    }
+
+    fn unknown_variant(variant: &str, expected: &'static [&'static str]) -> Self {
+        if expected.is_empty() {
+            Error::new(
+                ErrorKind::Parse,
+                format_args!("unknown variant `{}`, there are no variants", variant),
+            )
+        } else {
+            Error::new(
+                ErrorKind::Parse,
+                format_args!("unknown variant `{}`, expected {}", variant, OneOf(expected)),
+            )
+        }
+    }
+
+    fn unknown_field(field: &str, expected: &'static [&'static str]) -> Self {
+        if expected.is_empty() {
+            Error::new(
+                ErrorKind::Unsupported,
+                format_args!("unknown field `{}`, there are no fields", field),
+            )
+        } else {
+            Error::new(
+                ErrorKind::Unsupported,
+                format_args!("unknown field `{}`, expected {}", field, OneOf(expected)),
+            )
+        }
+    }
+
+    fn missing_field(field: &'static str) -> Self {
+        Error::new(ErrorKind::MissingField, format_args!("missing field `{}`", field))
+            .with_key(field)
+    }
+
+    fn duplicate_field(field: &'static str) -> Self {
+        Error::new(ErrorKind::Duplicate, format_args!("duplicate field `{}`", field))
+    }
+}
+
+/// Formats a list of names like serde does for unknown variants and fields.
+struct OneOf(&'static [&'static str]);
+
+impl fmt::Display for OneOf {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0 {
+            [] => Ok(()),
+            [a] => write!(f, "`{}`", a),
+            [a, b] => write!(f, "`{}` or `{}`", a, b),
+            [first, rest @ ..] => {
+                write!(f, "one of `{}`", first)?;
+                for name in rest {
+                    write!(f, ", `{}`", name)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
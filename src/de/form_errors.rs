@@ -0,0 +1,222 @@
+use alloc::{
+    borrow::Cow,
+    collections::{BTreeMap, BTreeSet},
+    string::{String, ToString as _},
+    vec::Vec,
+};
+use core::{fmt, slice};
+#[cfg(feature = "std")]
+use std::error;
+
+use serde_core::de::Deserialize;
+
+use super::{
+    collect::Collect, nested, part::Part, placeholder::WithoutField, utils::struct_fields,
+    Deserializer, Error, ErrorKind, Input,
+};
+use crate::KeyNotation;
+
+/// All errors found while deserializing a form, along with the submitted
+/// values.
+///
+/// Returned by [`Deserializer::collect_errors`] and
+/// [`from_str_collect_errors`](super::from_str_collect_errors).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FormErrors {
+    errors: Vec<FieldError>,
+    pairs: Vec<(String, String)>,
+}
+
+/// An error about one field of a form, along with the values that were
+/// submitted for it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FieldError {
+    error: Error,
+    values: Vec<String>,
+}
+
+impl FormErrors {
+    /// Returns the number of errors.
+    pub fn len(&self) -> usize {
+        self.errors.len()
+    }
+
+    /// Returns `true` if there are no errors.
+    ///
+    /// This is never the case for a `FormErrors` returned by this crate.
+    pub fn is_empty(&self) -> bool {
+        self.errors.is_empty()
+    }
+
+    /// Returns an iterator over the errors, in the order they were found.
+    pub fn iter(&self) -> slice::Iter<'_, FieldError> {
+        self.errors.iter()
+    }
+
+    /// Returns the error for the given key, if there is one.
+    pub fn get(&self, key: &str) -> Option<&FieldError> {
+        self.errors.iter().find(|e| e.key() == Some(key))
+    }
+
+    /// Returns the first value submitted for the given key, whether or not
+    /// there is an error for it.
+    ///
+    /// This is useful for showing what the user entered when rendering the
+    /// form again.
+    pub fn value(&self, key: &str) -> Option<&str> {
+        self.pairs.iter().find(|(k, _)| k == key).map(|(_, v)| v.as_str())
+    }
+
+    /// Returns all values submitted for the given key.
+    pub fn values<'a>(&'a self, key: &'a str) -> impl Iterator<Item = &'a str> + 'a {
+        self.pairs.iter().filter(move |(k, _)| k == key).map(|(_, v)| v.as_str())
+    }
+}
+
+impl FieldError {
+    /// Returns the key of the field, if the error is about a field.
+    pub fn key(&self) -> Option<&str> {
+        self.error.key()
+    }
+
+    /// Returns the error.
+    pub fn error(&self) -> &Error {
+        &self.error
+    }
+
+    /// Returns the percent-decoded values that were submitted for the field.
+    ///
+    /// This is empty for missing fields and errors that are not about a
+    /// field.
+    pub fn values(&self) -> &[String] {
+        &self.values
+    }
+}
+
+impl<'a> IntoIterator for &'a FormErrors {
+    type Item = &'a FieldError;
+    type IntoIter = slice::Iter<'a, FieldError>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl fmt::Display for FormErrors {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, error) in self.errors.iter().enumerate() {
+            if i > 0 {
+                f.write_str("; ")?;
+            }
+            error.fmt(f)?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for FieldError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.key() {
+            Some(key) => write!(f, "{}: {}", key, self.error),
+            None => self.error.fmt(f),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl error::Error for FormErrors {}
+
+#[cfg(feature = "std")]
+impl error::Error for FieldError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        Some(&self.error)
+    }
+}
+
+#[cfg(not(feature = "std"))]
+impl serde_core::de::StdError for FormErrors {}
+
+#[cfg(not(feature = "std"))]
+impl serde_core::de::StdError for FieldError {}
+
+/// Deserializes `T` from the pairs in one pass, recording the error of every
+/// value that fails and going on with a placeholder for it, see `Collect`.
+///
+/// Missing fields are only noticed once a struct is complete, and only the
+/// first one is reported. So for the top-level struct, each field that is not
+/// in the input is checked on its own by deserializing `T` from placeholders
+/// without it. This depends on the number of fields of `T`, but not on the
+/// input.
+pub(super) fn collect_errors<'de, T: Deserialize<'de>>(
+    deserializer: Deserializer<'de>,
+) -> Result<T, FormErrors> {
+    let Deserializer { key_notation, duplicate_keys, infer_scalars, limits, .. } = deserializer;
+    let pairs = match deserializer.checked_pairs().collect::<Result<Vec<_>, _>>() {
+        Ok(pairs) => pairs,
+        Err(error) => {
+            let errors = Vec::from([FieldError { error, values: Vec::new() }]);
+            return Err(FormErrors { errors, pairs: Vec::new() });
+        }
+    };
+
+    let deserializer = Deserializer::with_input(Input::Pairs(pairs.clone().into_iter()))
+        .key_notation(key_notation)
+        .duplicate_keys(duplicate_keys)
+        .infer_scalars(infer_scalars)
+        .limits(limits);
+    let mut errors = Vec::new();
+    match T::deserialize(Collect::new(deserializer, &mut errors)) {
+        Ok(value) if errors.is_empty() => return Ok(value),
+        Ok(_) => {}
+        Err(error) if error.kind() == ErrorKind::MissingField => {
+            let missing = missing_fields::<T>(&pairs, key_notation);
+            if missing.is_empty() {
+                errors.push(error);
+            }
+            errors.extend(missing);
+        }
+        Err(error) => errors.push(error),
+    }
+
+    // The submitted values of the keys with errors, in one pass over the pairs.
+    let mut values: BTreeMap<&str, Vec<String>> =
+        errors.iter().filter_map(Error::key).map(|key| (key, Vec::new())).collect();
+    for (key, value) in &pairs {
+        if let Some(values) = values.get_mut(&**key) {
+            values.push(value.value.to_string());
+        }
+    }
+    let values: Vec<_> = errors
+        .iter()
+        .map(|error| error.key().and_then(|key| values.get(key)).cloned().unwrap_or_default())
+        .collect();
+
+    let errors = errors.into_iter().zip(values).map(|(error, values)| FieldError { error, values });
+    let pairs = pairs.into_iter().map(|(k, v)| (k.into_owned(), v.value.into_owned())).collect();
+    Err(FormErrors { errors: errors.collect(), pairs })
+}
+
+/// Returns the errors for the fields of the struct `T` that are not in the
+/// input and are required.
+fn missing_fields<'de, T: Deserialize<'de>>(
+    pairs: &[(Cow<'_, str>, Part<'_>)],
+    key_notation: KeyNotation,
+) -> Vec<Error> {
+    let fields = match struct_fields::<T>() {
+        Some(fields) => fields,
+        None => return Vec::new(),
+    };
+
+    let keys: BTreeSet<&str> =
+        pairs.iter().map(|(key, _)| nested::top_level_key(key, key_notation)).collect();
+    fields
+        .iter()
+        .filter(|field| !keys.contains(*field))
+        .filter_map(|&field| match T::deserialize(WithoutField(field)) {
+            Err(error) if error.kind() == ErrorKind::MissingField && error.key() == Some(field) => {
+                Some(error)
+            }
+            _ => None,
+        })
+        .collect()
+}
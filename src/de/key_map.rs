@@ -0,0 +1,134 @@
+//! The maps used for grouping the pairs of a form by key, in the order of
+//! their first occurrence.
+//!
+//! With `std`, these are `IndexMap`s with its randomly seeded hasher. Without
+//! it, there is no hasher that is safe to use with keys chosen by an attacker,
+//! so a `BTreeMap` that remembers the insertion order is used instead.
+
+#[cfg(feature = "std")]
+pub(crate) use self::indexed::*;
+#[cfg(not(feature = "std"))]
+pub(crate) use self::ordered::*;
+
+#[cfg(feature = "std")]
+mod indexed {
+    pub(crate) use indexmap::map::Entry;
+
+    pub(crate) type KeyMap<K, V> =
+        indexmap::IndexMap<K, V, std::collections::hash_map::RandomState>;
+}
+
+#[cfg(not(feature = "std"))]
+mod ordered {
+    use alloc::{
+        collections::btree_map::{self, BTreeMap},
+        vec::{self, Vec},
+    };
+    use core::{borrow::Borrow, iter};
+
+    /// A map that iterates in insertion order, like an `IndexMap`.
+    pub(crate) struct KeyMap<K, V>(BTreeMap<K, (usize, V)>);
+
+    impl<K, V> Default for KeyMap<K, V> {
+        fn default() -> Self {
+            KeyMap(BTreeMap::new())
+        }
+    }
+
+    impl<K: Ord, V> KeyMap<K, V> {
+        pub fn entry(&mut self, key: K) -> Entry<'_, K, V> {
+            let index = self.0.len();
+            match self.0.entry(key) {
+                btree_map::Entry::Vacant(entry) => Entry::Vacant(VacantEntry { entry, index }),
+                btree_map::Entry::Occupied(entry) => Entry::Occupied(OccupiedEntry(entry)),
+            }
+        }
+
+        pub fn get<Q>(&self, key: &Q) -> Option<&V>
+        where
+            K: Borrow<Q>,
+            Q: Ord + ?Sized,
+        {
+            self.0.get(key).map(|(_, value)| value)
+        }
+
+        pub fn insert(&mut self, key: K, value: V) {
+            match self.entry(key) {
+                Entry::Vacant(entry) => {
+                    entry.insert(value);
+                }
+                Entry::Occupied(entry) => *entry.into_mut() = value,
+            }
+        }
+
+        pub fn len(&self) -> usize {
+            self.0.len()
+        }
+    }
+
+    impl<K: Ord, V> FromIterator<(K, V)> for KeyMap<K, V> {
+        fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+            let mut map = KeyMap::default();
+            for (key, value) in iter {
+                map.insert(key, value);
+            }
+            map
+        }
+    }
+
+    impl<K, V> IntoIterator for KeyMap<K, V> {
+        type Item = (K, V);
+        type IntoIter = iter::Map<vec::IntoIter<(K, (usize, V))>, fn((K, (usize, V))) -> (K, V)>;
+
+        fn into_iter(self) -> Self::IntoIter {
+            let mut entries: Vec<_> = self.0.into_iter().collect();
+            entries.sort_unstable_by_key(|(_, (index, _))| *index);
+            entries.into_iter().map(|(key, (_, value))| (key, value))
+        }
+    }
+
+    pub(crate) enum Entry<'a, K, V> {
+        Vacant(VacantEntry<'a, K, V>),
+        Occupied(OccupiedEntry<'a, K, V>),
+    }
+
+    impl<'a, K: Ord, V> Entry<'a, K, V> {
+        pub fn or_insert_with(self, default: impl FnOnce() -> V) -> &'a mut V {
+            match self {
+                Entry::Vacant(entry) => entry.insert(default()),
+                Entry::Occupied(entry) => entry.into_mut(),
+            }
+        }
+    }
+
+    pub(crate) struct VacantEntry<'a, K, V> {
+        entry: btree_map::VacantEntry<'a, K, (usize, V)>,
+        index: usize,
+    }
+
+    impl<'a, K: Ord, V> VacantEntry<'a, K, V> {
+        pub fn insert(self, value: V) -> &'a mut V {
+            &mut self.entry.insert((self.index, value)).1
+        }
+    }
+
+    pub(crate) struct OccupiedEntry<'a, K, V>(btree_map::OccupiedEntry<'a, K, (usize, V)>);
+
+    impl<'a, K: Ord, V> OccupiedEntry<'a, K, V> {
+        pub fn key(&self) -> &K {
+            self.0.key()
+        }
+
+        pub fn get(&self) -> &V {
+            &self.0.get().1
+        }
+
+        pub fn get_mut(&mut self) -> &mut V {
+            &mut self.0.get_mut().1
+        }
+
+        pub fn into_mut(self) -> &'a mut V {
+            &mut self.0.into_mut().1
+        }
+    }
+}
@@ -0,0 +1,244 @@
+use alloc::borrow::Cow;
+
+use super::{part::Part, Error, ErrorKind};
+
+/// The largest index accepted for sequence elements by default, as in
+/// `items[1000]`.
+const DEFAULT_MAX_INDEX: usize = 1000;
+
+/// Limits for deserializing untrusted input.
+///
+/// Apart from the sequence index, all limits are unset by default. Limits are
+/// checked while the input is parsed, before the pairs are grouped by key, and
+/// deserialization stops with an error at the first one that is exceeded.
+///
+/// ```
+/// use serde::Deserialize;
+/// use serde_html_form::{de::Limits, Deserializer};
+///
+/// #[derive(Debug, Deserialize)]
+/// struct Form {
+///     tags: Vec<String>,
+/// }
+///
+/// let limits = Limits::new().max_pairs(100).max_value_len(1024).max_values_per_key(2);
+/// let deserializer = Deserializer::from_bytes(b"tags=a&tags=b&tags=c").limits(limits);
+/// assert_eq!(
+///     Form::deserialize(deserializer).unwrap_err().to_string(),
+///     "limit exceeded: `tags` has more than 2 values"
+/// );
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Limits {
+    max_pairs: Option<usize>,
+    max_key_len: Option<usize>,
+    max_value_len: Option<usize>,
+    max_values_per_key: Option<usize>,
+    max_total_len: Option<usize>,
+    max_depth: Option<usize>,
+    max_index: usize,
+}
+
+impl Limits {
+    /// Returns a new `Limits` with only the default sequence index limit set.
+    pub fn new() -> Self {
+        Limits {
+            max_pairs: None,
+            max_key_len: None,
+            max_value_len: None,
+            max_values_per_key: None,
+            max_total_len: None,
+            max_depth: None,
+            max_index: DEFAULT_MAX_INDEX,
+        }
+    }
+
+    /// Sets the maximum number of pairs in the input.
+    pub fn max_pairs(mut self, max: usize) -> Self {
+        self.max_pairs = Some(max);
+        self
+    }
+
+    /// Sets the maximum length of a key after percent-decoding, in bytes.
+    pub fn max_key_len(mut self, max: usize) -> Self {
+        self.max_key_len = Some(max);
+        self
+    }
+
+    /// Sets the maximum length of a value after percent-decoding, in bytes.
+    pub fn max_value_len(mut self, max: usize) -> Self {
+        self.max_value_len = Some(max);
+        self
+    }
+
+    /// Sets the maximum number of values for the same key.
+    pub fn max_values_per_key(mut self, max: usize) -> Self {
+        self.max_values_per_key = Some(max);
+        self
+    }
+
+    /// Sets the maximum length of all keys and values together after
+    /// percent-decoding, in bytes.
+    pub fn max_total_len(mut self, max: usize) -> Self {
+        self.max_total_len = Some(max);
+        self
+    }
+
+    /// Sets the maximum number of segments of a nested key, so
+    /// `user[address][city]` has a depth of three.
+    ///
+    /// This only applies to nested [key notations](crate::KeyNotation).
+    pub fn max_depth(mut self, max: usize) -> Self {
+        self.max_depth = Some(max);
+        self
+    }
+
+    /// Sets the largest index accepted for sequence elements with nested
+    /// [key notations](crate::KeyNotation), as in `items[1000]`.
+    ///
+    /// Defaults to 1000.
+    pub fn max_index(mut self, max: usize) -> Self {
+        self.max_index = max;
+        self
+    }
+
+    pub(crate) fn check_values_per_key(&self, key: &str, len: usize) -> Result<(), Error> {
+        match self.max_values_per_key {
+            Some(max) if len > max => {
+                Err(limit_exceeded(format_args!("`{}` has more than {} values", key, max))
+                    .with_key(key))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    pub(crate) fn check_depth(&self, key: &str, depth: usize) -> Result<(), Error> {
+        match self.max_depth {
+            Some(max) if depth > max => Err(limit_exceeded(format_args!(
+                "`{}` is nested deeper than the limit of {}",
+                key, max
+            ))
+            .with_key(key)),
+            _ => Ok(()),
+        }
+    }
+
+    /// Whether any of the limits that are checked for each pair is set.
+    pub(crate) fn checks_pairs(&self) -> bool {
+        self.max_pairs.is_some()
+            || self.max_key_len.is_some()
+            || self.max_value_len.is_some()
+            || self.max_total_len.is_some()
+    }
+
+    pub(crate) fn index_limit(&self) -> usize {
+        self.max_index
+    }
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+pub(crate) fn limit_exceeded(msg: core::fmt::Arguments<'_>) -> Error {
+    Error::new(ErrorKind::LimitExceeded, format_args!("limit exceeded: {}", msg))
+}
+
+/// Checks the limits on the pairs of a form as they are parsed.
+#[derive(Debug, Default)]
+pub(crate) struct PairCounter {
+    limits: Limits,
+    pairs: usize,
+    total_len: usize,
+}
+
+impl PairCounter {
+    pub fn new(limits: Limits) -> Self {
+        PairCounter { limits, pairs: 0, total_len: 0 }
+    }
+
+    /// Checks the next pair, returning an error with its key and span if it
+    /// exceeds the limits.
+    pub fn check(&mut self, key: &str, value: &Part<'_>) -> Result<(), Error> {
+        if !self.limits.checks_pairs() {
+            return Ok(());
+        }
+        self.check_pair(key, value).map_err(|e| e.with_key(key).with_span(value.span()))
+    }
+
+    fn check_pair(&mut self, key: &str, value: &Part<'_>) -> Result<(), Error> {
+        let limits = &self.limits;
+
+        self.pairs += 1;
+        if let Some(max) = limits.max_pairs {
+            if self.pairs > max {
+                return Err(limit_exceeded(format_args!("more than {} pairs", max)));
+            }
+        }
+
+        if let Some(max) = limits.max_key_len {
+            if key.len() > max {
+                return Err(limit_exceeded(format_args!("key is longer than {} bytes", max)));
+            }
+        }
+
+        if let Some(max) = limits.max_value_len {
+            if value.len() > max {
+                return Err(limit_exceeded(format_args!(
+                    "value of `{}` is longer than {} bytes",
+                    key, max
+                )));
+            }
+        }
+
+        self.total_len = self.total_len.saturating_add(key.len() + value.len());
+        if let Some(max) = limits.max_total_len {
+            if self.total_len > max {
+                return Err(limit_exceeded(format_args!(
+                    "keys and values are longer than {} bytes in total",
+                    max
+                )));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// An iterator over the pairs of a form that checks them against `Limits`.
+pub(super) struct Checked<I> {
+    pairs: I,
+    /// Unset if no limit is checked for each pair.
+    counter: Option<PairCounter>,
+}
+
+impl<I> Checked<I> {
+    pub fn new(pairs: I, limits: Limits) -> Self {
+        let counter = limits.checks_pairs().then(|| PairCounter::new(limits));
+        Checked { pairs, counter }
+    }
+
+    pub fn get_ref(&self) -> &I {
+        &self.pairs
+    }
+}
+
+impl<'de, I> Iterator for Checked<I>
+where
+    I: Iterator<Item = Result<(Cow<'de, str>, Part<'de>), Error>>,
+{
+    type Item = Result<(Cow<'de, str>, Part<'de>), Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let pair = self.pairs.next()?;
+        match &mut self.counter {
+            Some(counter) => Some(pair.and_then(|(key, value)| {
+                counter.check(&key, &value)?;
+                Ok((key, value))
+            })),
+            None => Some(pair),
+        }
+    }
+}
@@ -0,0 +1,542 @@
+use alloc::{
+    borrow::{Cow, ToOwned as _},
+    vec::Vec,
+};
+
+use serde_core::de::{self, value::SeqDeserializer, Error as _, IntoDeserializer, Unexpected};
+
+use super::{
+    entries::Entries,
+    key_map::{self, KeyMap},
+    limits::{limit_exceeded, Limits},
+    part::Part,
+    val_or_vec::Values,
+    DuplicateKeys, Error, ErrorKind,
+};
+use crate::KeyNotation;
+
+pub(super) type NodeMap<'de> = KeyMap<Part<'de>, Node<'de>>;
+
+/// A value in the tree built from a form with nested keys.
+pub(super) enum Node<'de> {
+    Leaf(Values<'de>),
+    Map {
+        map: NodeMap<'de>,
+        /// The largest index accepted if the map is deserialized as a sequence.
+        max_index: usize,
+    },
+}
+
+/// Groups the pairs of a form by the paths encoded in their keys.
+pub(super) fn group_nested<'de, I>(
+    pairs: I,
+    notation: KeyNotation,
+    duplicate_keys: DuplicateKeys,
+    infer_scalars: bool,
+    limits: &Limits,
+) -> Result<NodeMap<'de>, Error>
+where
+    I: IntoIterator<Item = Result<(Cow<'de, str>, Part<'de>), Error>>,
+{
+    let mut res = NodeMap::default();
+
+    for pair in pairs {
+        let (key, value) = pair?;
+        let leaf = Leaf { key: &key, value, duplicate_keys, infer_scalars, limits };
+        match key {
+            Cow::Borrowed(k) => insert(&mut res, split_key(k, notation).map(Cow::Borrowed), leaf)?,
+            Cow::Owned(ref k) => {
+                let segments = split_key(k, notation).map(|s| Cow::Owned(s.to_owned()));
+                insert(&mut res, segments, leaf)?;
+            }
+        }
+    }
+
+    Ok(res)
+}
+
+/// A value to be inserted into the tree, along with its full key.
+struct Leaf<'a, 'de> {
+    key: &'a Cow<'de, str>,
+    value: Part<'de>,
+    duplicate_keys: DuplicateKeys,
+    infer_scalars: bool,
+    limits: &'a Limits,
+}
+
+fn insert<'de, I>(
+    mut map: &mut NodeMap<'de>,
+    mut segments: I,
+    leaf: Leaf<'_, 'de>,
+) -> Result<(), Error>
+where
+    I: Iterator<Item = Cow<'de, str>>,
+{
+    use key_map::Entry::*;
+
+    // `split_key` always yields at least one segment
+    let mut segment = segments.next().unwrap_or_default();
+    let mut depth = 1;
+    loop {
+        match segments.next() {
+            None => {
+                match map.entry(Part::new(segment)) {
+                    Vacant(v) => {
+                        let values = Values::new(leaf.value, leaf.duplicate_keys)
+                            .infer_scalars(leaf.infer_scalars)
+                            .with_key(leaf.key.clone());
+                        v.insert(Node::Leaf(values));
+                    }
+                    Occupied(o) => match o.into_mut() {
+                        Node::Leaf(values) => {
+                            values.push(leaf.value);
+                            leaf.limits.check_values_per_key(leaf.key, values.len())?;
+                        }
+                        Node::Map { .. } => return Err(conflicting_key(&leaf)),
+                    },
+                }
+
+                return Ok(());
+            }
+            Some(next) => {
+                depth += 1;
+                leaf.limits.check_depth(leaf.key, depth)?;
+
+                let max_index = leaf.limits.index_limit();
+                let node = map
+                    .entry(Part::new(segment))
+                    .or_insert_with(|| Node::Map { map: NodeMap::default(), max_index });
+                map = match node {
+                    Node::Map { map, .. } => map,
+                    Node::Leaf(_) => return Err(conflicting_key(&leaf)),
+                };
+                segment = next;
+            }
+        }
+    }
+}
+
+fn conflicting_key(leaf: &Leaf<'_, '_>) -> Error {
+    Error::new(
+        ErrorKind::Unsupported,
+        format_args!("key `{}` conflicts with a previous key", leaf.key),
+    )
+    .with_key(leaf.key)
+    .with_span(leaf.value.span())
+}
+
+/// Returns the first path segment of a key, like `user` for `user[name]`.
+pub(super) fn top_level_key(key: &str, notation: KeyNotation) -> &str {
+    split_key(key, notation).next().unwrap_or(key)
+}
+
+/// Splits a key into its path segments.
+///
+/// Keys that are not well-formed paths in the given notation are not split.
+fn split_key(key: &str, notation: KeyNotation) -> Segments<'_> {
+    let valid = match notation {
+        KeyNotation::Flat => false,
+        KeyNotation::Brackets => is_bracket_path(key),
+        KeyNotation::Dots => is_dotted_path(key),
+    };
+
+    if valid {
+        Segments { rest: key, notation }
+    } else {
+        Segments { rest: key, notation: KeyNotation::Flat }
+    }
+}
+
+/// Checks whether `key` looks like `head[a][b]…`, where only the last
+/// bracketed segment may be empty.
+fn is_bracket_path(key: &str) -> bool {
+    let mut rest = match key.find('[') {
+        Some(0) | None => return false,
+        Some(idx) if key[..idx].contains(']') => return false,
+        Some(idx) => &key[idx + 1..],
+    };
+
+    loop {
+        let end = match rest.find(']') {
+            Some(end) => end,
+            None => return false,
+        };
+        let segment = &rest[..end];
+        if segment.contains('[') {
+            return false;
+        }
+
+        rest = &rest[end + 1..];
+        if rest.is_empty() {
+            return true;
+        }
+
+        rest = match rest.strip_prefix('[') {
+            Some(rest) if !segment.is_empty() => rest,
+            _ => return false,
+        };
+    }
+}
+
+/// Checks whether `key` looks like `a.b[c].d…`, where bracketed segments
+/// are optional and only the last one may be empty.
+fn is_dotted_path(key: &str) -> bool {
+    let mut rest = key;
+    let mut nested = false;
+
+    loop {
+        let end = rest.find(&['.', '['][..]).unwrap_or(rest.len());
+        let (segment, tail) = rest.split_at(end);
+        if segment.is_empty() || segment.contains(']') {
+            return false;
+        }
+        rest = tail;
+
+        while let Some(tail) = rest.strip_prefix('[') {
+            let end = match tail.find(']') {
+                Some(end) => end,
+                None => return false,
+            };
+            let segment = &tail[..end];
+            rest = &tail[end + 1..];
+            nested = true;
+
+            if segment.contains('[') || (segment.is_empty() && !rest.is_empty()) {
+                return false;
+            }
+        }
+
+        match rest.strip_prefix('.') {
+            Some(tail) => {
+                rest = tail;
+                nested = true;
+            }
+            None => return nested && rest.is_empty(),
+        }
+    }
+}
+
+struct Segments<'a> {
+    rest: &'a str,
+    notation: KeyNotation,
+}
+
+impl<'a> Iterator for Segments<'a> {
+    type Item = &'a str;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.rest.is_empty() {
+            return None;
+        }
+
+        if let (KeyNotation::Brackets | KeyNotation::Dots, Some(rest)) =
+            (self.notation, self.rest.strip_prefix('['))
+        {
+            // Validated by `is_bracket_path` / `is_dotted_path`
+            let end = rest.find(']').unwrap_or(rest.len());
+            self.rest = rest.get(end + 1..).unwrap_or_default();
+
+            let segment = &rest[..end];
+            if segment.is_empty() {
+                // trailing `[]`
+                return None;
+            }
+            return Some(segment);
+        }
+
+        let (rest, end) = match self.notation {
+            KeyNotation::Flat => (self.rest, None),
+            KeyNotation::Brackets => (self.rest, self.rest.find('[')),
+            KeyNotation::Dots => {
+                let rest = self.rest.strip_prefix('.').unwrap_or(self.rest);
+                (rest, rest.find(&['.', '['][..]))
+            }
+        };
+        let (segment, rest) = rest.split_at(end.unwrap_or(rest.len()));
+        self.rest = rest;
+        Some(segment)
+    }
+}
+
+/// Turns a map of sequence indices like `{"1": b, "0": a}` into the list of
+/// its values `[a, b]`.
+///
+/// Elements are ordered by index. Gaps are skipped, so `{"0": a, "5": b}`
+/// also results in `[a, b]`.
+fn into_elements(map: NodeMap<'_>, max_index: usize) -> Result<Vec<Node<'_>>, Error> {
+    let mut elements = Vec::with_capacity(map.len());
+    for (Part { value: key, .. }, node) in map {
+        elements.push((parse_index(&key, max_index)?, node));
+    }
+
+    elements.sort_unstable_by_key(|(index, _)| *index);
+    Ok(elements.into_iter().map(|(_, node)| node).collect())
+}
+
+fn parse_index(key: &str, max_index: usize) -> Result<usize, Error> {
+    let is_canonical_number = !key.is_empty()
+        && key.bytes().all(|b| b.is_ascii_digit())
+        && (key == "0" || !key.starts_with('0'));
+    if !is_canonical_number {
+        return Err(Error::new(ErrorKind::Parse, format_args!("invalid sequence index `{}`", key)));
+    }
+
+    match key.parse() {
+        Ok(index) if index <= max_index => Ok(index),
+        _ => Err(limit_exceeded(format_args!(
+            "sequence index {} is larger than the maximum of {}",
+            key, max_index
+        ))),
+    }
+}
+
+impl<'de> IntoDeserializer<'de, Error> for Node<'de> {
+    type Deserializer = Self;
+
+    fn into_deserializer(self) -> Self::Deserializer {
+        self
+    }
+}
+
+macro_rules! forward_to_leaf {
+    ($($method:ident,)*) => {
+        $(
+            fn $method<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+                where V: de::Visitor<'de>
+            {
+                match self {
+                    Node::Leaf(values) => values.$method(visitor),
+                    Node::Map { .. } => Err(Error::invalid_type(Unexpected::Map, &visitor)),
+                }
+            }
+        )*
+    }
+}
+
+impl<'de> de::Deserializer<'de> for Node<'de> {
+    type Error = Error;
+
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        match self {
+            Node::Leaf(values) => values.deserialize_any(visitor),
+            Node::Map { map, .. } => visitor.visit_map(Entries::new(map.into_iter())),
+        }
+    }
+
+    fn deserialize_map<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        match self {
+            Node::Leaf(values) => values.deserialize_map(visitor),
+            Node::Map { map, .. } => visitor.visit_map(Entries::new(map.into_iter())),
+        }
+    }
+
+    fn deserialize_struct<V>(
+        self,
+        name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        match self {
+            Node::Leaf(values) => values.deserialize_struct(name, fields, visitor),
+            Node::Map { map, .. } => visitor.visit_map(Entries::new(map.into_iter())),
+        }
+    }
+
+    fn deserialize_option<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        match self {
+            Node::Leaf(values) => values.deserialize_option(visitor),
+            Node::Map { .. } => visitor.visit_some(self),
+        }
+    }
+
+    fn deserialize_newtype_struct<V>(
+        self,
+        name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        match self {
+            Node::Leaf(values) => values.deserialize_newtype_struct(name, visitor),
+            Node::Map { .. } => visitor.visit_newtype_struct(self),
+        }
+    }
+
+    fn deserialize_enum<V>(
+        self,
+        name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        match self {
+            Node::Leaf(values) => values.deserialize_enum(name, variants, visitor),
+            Node::Map { .. } => Err(Error::invalid_type(Unexpected::Map, &visitor)),
+        }
+    }
+
+    fn deserialize_seq<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        match self {
+            Node::Leaf(values) => values.deserialize_seq(visitor),
+            Node::Map { map, max_index } => {
+                visitor.visit_seq(SeqDeserializer::new(into_elements(map, max_index)?.into_iter()))
+            }
+        }
+    }
+
+    fn deserialize_tuple<V>(self, len: usize, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        match self {
+            Node::Leaf(values) => values.deserialize_tuple(len, visitor),
+            Node::Map { map, max_index } => {
+                visitor.visit_seq(SeqDeserializer::new(into_elements(map, max_index)?.into_iter()))
+            }
+        }
+    }
+
+    fn deserialize_tuple_struct<V>(
+        self,
+        name: &'static str,
+        len: usize,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        match self {
+            Node::Leaf(values) => values.deserialize_tuple_struct(name, len, visitor),
+            Node::Map { .. } => Err(Error::invalid_type(Unexpected::Map, &visitor)),
+        }
+    }
+
+    fn deserialize_unit_struct<V>(
+        self,
+        name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        match self {
+            Node::Leaf(values) => values.deserialize_unit_struct(name, visitor),
+            Node::Map { .. } => Err(Error::invalid_type(Unexpected::Map, &visitor)),
+        }
+    }
+
+    fn deserialize_ignored_any<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        visitor.visit_unit()
+    }
+
+    forward_to_leaf! {
+        deserialize_bool,
+        deserialize_char,
+        deserialize_str,
+        deserialize_string,
+        deserialize_bytes,
+        deserialize_byte_buf,
+        deserialize_unit,
+        deserialize_u8,
+        deserialize_u16,
+        deserialize_u32,
+        deserialize_u64,
+        deserialize_i8,
+        deserialize_i16,
+        deserialize_i32,
+        deserialize_i64,
+        deserialize_f32,
+        deserialize_f64,
+        deserialize_identifier,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec::Vec;
+
+    use super::split_key;
+    use crate::KeyNotation;
+
+    fn segments(key: &str) -> Vec<&str> {
+        split_key(key, KeyNotation::Brackets).collect()
+    }
+
+    fn dotted_segments(key: &str) -> Vec<&str> {
+        split_key(key, KeyNotation::Dots).collect()
+    }
+
+    #[test]
+    fn split_brackets() {
+        assert_eq!(segments("user"), ["user"]);
+        assert_eq!(segments("user[name]"), ["user", "name"]);
+        assert_eq!(segments("a[b][c]"), ["a", "b", "c"]);
+        assert_eq!(segments("tags[]"), ["tags"]);
+        assert_eq!(segments("a[b][]"), ["a", "b"]);
+    }
+
+    #[test]
+    fn split_malformed_brackets() {
+        assert_eq!(segments("a[b"), ["a[b"]);
+        assert_eq!(segments("[a]"), ["[a]"]);
+        assert_eq!(segments("a[b]c"), ["a[b]c"]);
+        assert_eq!(segments("a[][b]"), ["a[][b]"]);
+        assert_eq!(segments("a[[b]]"), ["a[[b]]"]);
+        assert_eq!(segments("a]b"), ["a]b"]);
+    }
+
+    #[test]
+    fn split_dots() {
+        assert_eq!(dotted_segments("user"), ["user"]);
+        assert_eq!(dotted_segments("submit.x"), ["submit", "x"]);
+        assert_eq!(dotted_segments("a.b.c"), ["a", "b", "c"]);
+        assert_eq!(dotted_segments("a[b]"), ["a", "b"]);
+        assert_eq!(dotted_segments("items[0].name"), ["items", "0", "name"]);
+        assert_eq!(dotted_segments("items.0.name"), ["items", "0", "name"]);
+        assert_eq!(dotted_segments("dict[a.b][]"), ["dict", "a.b"]);
+    }
+
+    #[test]
+    fn split_malformed_dots() {
+        assert_eq!(dotted_segments(".a"), [".a"]);
+        assert_eq!(dotted_segments("a."), ["a."]);
+        assert_eq!(dotted_segments("a..b"), ["a..b"]);
+        assert_eq!(dotted_segments("a.[b]"), ["a.[b]"]);
+        assert_eq!(dotted_segments("a[b]c"), ["a[b]c"]);
+        assert_eq!(dotted_segments("a[][b]"), ["a[][b]"]);
+    }
+
+    #[test]
+    fn parse_index() {
+        assert_eq!(super::parse_index("0", 1000).ok(), Some(0));
+        assert_eq!(super::parse_index("17", 1000).ok(), Some(17));
+        assert_eq!(super::parse_index("1000", 1000).ok(), Some(1000));
+        assert!(super::parse_index("1001", 1000).is_err());
+        assert!(super::parse_index("99999999999999999999999", 1000).is_err());
+        assert!(super::parse_index("", 1000).is_err());
+        assert!(super::parse_index("01", 1000).is_err());
+        assert!(super::parse_index("+1", 1000).is_err());
+        assert!(super::parse_index("a", 1000).is_err());
+    }
+}
@@ -0,0 +1,179 @@
+use alloc::{borrow::Cow, vec::Vec};
+use core::ops::Range;
+
+use super::{
+    limits::{limit_exceeded, PairCounter},
+    part::Part,
+    raw::RawPairs,
+    Deserializer, Error, Input, Limits,
+};
+
+/// An incremental parser for `application/x-www-form-urlencoded` input that
+/// arrives in chunks.
+///
+/// Chunks can be split anywhere, including in the middle of a key, a value or
+/// a percent-encoded byte. Complete pairs are decoded as soon as they are
+/// available, so only the last, incomplete pair is buffered as raw bytes.
+///
+/// ```
+/// use serde::Deserialize;
+/// use serde_html_form::de::Parser;
+///
+/// #[derive(Debug, PartialEq, Deserialize)]
+/// struct Form {
+///     cheese: String,
+///     meat: String,
+/// }
+///
+/// let mut parser = Parser::new();
+/// parser.feed(b"cheese=comt%C")?;
+/// parser.feed(b"3%A9&me")?;
+/// parser.feed(b"at=ham")?;
+///
+/// let form = Form::deserialize(parser.finish()).unwrap();
+/// assert_eq!(form, Form { cheese: "comté".to_owned(), meat: "ham".to_owned() });
+/// # Ok::<(), serde_html_form::de::Error>(())
+/// ```
+#[derive(Debug, Default)]
+pub struct Parser {
+    pairs: Vec<(Cow<'static, str>, Part<'static>)>,
+    pending: Vec<u8>,
+    input_len: usize,
+    max_input_len: Option<usize>,
+    limits: Limits,
+    counter: PairCounter,
+    strict: bool,
+}
+
+impl Parser {
+    /// Returns a new `Parser`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the maximum number of bytes of raw input the parser accepts.
+    ///
+    /// Once more input than this is fed to the parser, [`feed`][Self::feed]
+    /// returns an error, so an oversized body can be rejected without reading
+    /// the rest of it.
+    ///
+    /// ```
+    /// use serde_html_form::de::Parser;
+    ///
+    /// let mut parser = Parser::new().max_input_len(8);
+    /// assert!(parser.feed(b"name=").is_ok());
+    /// assert_eq!(
+    ///     parser.feed(b"Jane").unwrap_err().to_string(),
+    ///     "limit exceeded: input is longer than 8 bytes"
+    /// );
+    /// ```
+    pub fn max_input_len(mut self, max_input_len: usize) -> Self {
+        self.max_input_len = Some(max_input_len);
+        self
+    }
+
+    /// Sets the [`Limits`] for the parsed pairs.
+    ///
+    /// Complete pairs are checked as they are fed to the parser, so input that
+    /// exceeds the limits is rejected as early as possible. The limits are
+    /// also applied by the `Deserializer` returned from
+    /// [`finish`][Self::finish].
+    pub fn limits(mut self, limits: Limits) -> Self {
+        self.limits = limits;
+        self.counter = PairCounter::new(limits);
+        self
+    }
+
+    /// Sets whether to reject malformed input, like
+    /// [`Deserializer::strict`].
+    ///
+    /// Complete pairs are checked as they are fed to the parser, and the
+    /// last pair when the `Deserializer` returned from
+    /// [`finish`][Self::finish] is used. Byte offsets in errors are relative
+    /// to the start of all input fed to the parser.
+    ///
+    /// ```
+    /// use serde_html_form::de::Parser;
+    ///
+    /// let mut parser = Parser::new().strict(true);
+    /// assert!(parser.feed(b"a=1&b=%").is_ok());
+    /// assert_eq!(
+    ///     parser.feed(b"zz&c=3").unwrap_err().to_string(),
+    ///     "invalid percent-escape `%zz` at byte 6"
+    /// );
+    /// ```
+    pub fn strict(mut self, strict: bool) -> Self {
+        self.strict = strict;
+        self
+    }
+
+    /// Feeds the next chunk of input to the parser.
+    ///
+    /// Returns an error if the input exceeds the limit set with
+    /// [`max_input_len`][Self::max_input_len] or one of the
+    /// [`limits`][Self::limits].
+    pub fn feed(&mut self, chunk: &[u8]) -> Result<(), Error> {
+        self.input_len = self.input_len.saturating_add(chunk.len());
+        if let Some(max) = self.max_input_len {
+            if self.input_len > max {
+                return Err(limit_exceeded(format_args!("input is longer than {} bytes", max)));
+            }
+        }
+
+        let pos = match chunk.iter().rposition(|&b| b == b'&') {
+            Some(pos) => pos,
+            None => {
+                self.pending.extend_from_slice(chunk);
+                return Ok(());
+            }
+        };
+
+        let (complete, rest) = chunk.split_at(pos);
+        let offset = self.input_len - chunk.len() - self.pending.len();
+        if self.pending.is_empty() {
+            self.decode(complete, offset)?;
+        } else {
+            let mut pending = core::mem::take(&mut self.pending);
+            pending.extend_from_slice(complete);
+            self.decode(&pending, offset)?;
+            pending.clear();
+            self.pending = pending;
+        }
+        self.pending.extend_from_slice(&rest[1..]);
+        Ok(())
+    }
+
+    /// Finishes parsing and returns a `Deserializer` for the pairs that were
+    /// fed to the parser.
+    pub fn finish(mut self) -> Deserializer<'static> {
+        let pending = core::mem::take(&mut self.pending);
+        let offset = self.input_len - pending.len();
+        for pair in self.raw_pairs(&pending, offset) {
+            match pair {
+                Ok(pair) => self.pairs.push(into_owned(pair)),
+                Err(e) => return Deserializer::with_input(Input::Failed(Some(e))),
+            }
+        }
+        Deserializer::with_input(Input::Pairs(self.pairs.into_iter())).limits(self.limits)
+    }
+
+    fn raw_pairs<'a>(&self, input: &'a [u8], offset: usize) -> RawPairs<'a> {
+        RawPairs::new(input).offset(offset).strict(self.strict)
+    }
+
+    fn decode(&mut self, input: &[u8], offset: usize) -> Result<(), Error> {
+        for pair in self.raw_pairs(input, offset) {
+            let (key, value) = into_owned(pair?);
+            self.counter.check(&key, &value)?;
+            self.pairs.push((key, value));
+        }
+        Ok(())
+    }
+}
+
+fn into_owned(
+    (key, value, span): (Cow<'_, [u8]>, Cow<'_, [u8]>, Range<usize>),
+) -> (Cow<'static, str>, Part<'static>) {
+    let key = Part::from_bytes(key).value.into_owned().into();
+    (key, Part::from_bytes(value).with_span(span).into_owned())
+}
@@ -1,19 +1,168 @@
-use alloc::borrow::Cow;
-use core::any::TypeId;
+use alloc::{borrow::Cow, boxed::Box, string::String};
+use core::{
+    any::TypeId,
+    cmp::Ordering,
+    hash::{Hash, Hasher},
+    ops::Range,
+    str,
+};
 
 use serde_core::{
-    de::{self, Error as _, IntoDeserializer},
+    de::{self, IntoDeserializer},
     forward_to_deserialize_any,
 };
 
 use crate::de::utils::non_static_type_id;
 
-use super::Error;
+use super::{Error, ErrorKind};
+
+/// A key or value of a form.
+///
+/// Parts are compared and hashed by their string only.
+#[derive(Clone, Debug)]
+pub(super) struct Part<'de> {
+    pub value: Cow<'de, str>,
+    /// The percent-decoded bytes if they are different from the UTF-8 of
+    /// `value`, which is the case for invalid UTF-8 and legacy encodings.
+    /// They are passed to `deserialize_bytes` as they are.
+    /// They are boxed as they are rarely needed.
+    pub raw: Option<Box<Cow<'de, [u8]>>>,
+    /// The byte span of the pair in the input, for errors.
+    span: Span,
+}
+
+impl<'de> Part<'de> {
+    pub fn new(value: Cow<'de, str>) -> Self {
+        Part { value, raw: None, span: Span::UNKNOWN }
+    }
+
+    /// Returns a part for `value` that was decoded from the bytes `raw`.
+    pub fn with_raw(value: Cow<'de, str>, raw: Cow<'de, [u8]>) -> Self {
+        Part { value, raw: Some(Box::new(raw)), span: Span::UNKNOWN }
+    }
+
+    pub fn with_span(mut self, span: Range<usize>) -> Self {
+        self.span = Span::new(span);
+        self
+    }
+
+    /// Returns the byte span of the pair in the input, if known.
+    pub fn span(&self) -> Option<Range<usize>> {
+        self.span.get()
+    }
+
+    /// Decodes percent-decoded bytes as UTF-8, replacing invalid sequences
+    /// with U+FFFD but keeping the original bytes.
+    pub fn from_bytes(bytes: Cow<'de, [u8]>) -> Self {
+        match bytes {
+            Cow::Borrowed(bytes) => match str::from_utf8(bytes) {
+                Ok(s) => Part::new(Cow::Borrowed(s)),
+                Err(_) => Part::with_raw(String::from_utf8_lossy(bytes), Cow::Borrowed(bytes)),
+            },
+            Cow::Owned(bytes) => match String::from_utf8(bytes) {
+                Ok(s) => Part::new(Cow::Owned(s)),
+                Err(e) => {
+                    let bytes = e.into_bytes();
+                    let s = String::from_utf8_lossy(&bytes).into_owned();
+                    Part::with_raw(Cow::Owned(s), Cow::Owned(bytes))
+                }
+            },
+        }
+    }
+
+    pub fn into_owned(self) -> Part<'static> {
+        Part {
+            value: Cow::Owned(self.value.into_owned()),
+            raw: self.raw.map(|b| Box::new(Cow::Owned((*b).into_owned()))),
+            span: self.span,
+        }
+    }
+
+    /// Like `deserialize_any`, but visits `true`, `false` and values that
+    /// look like JSON numbers as booleans and numbers instead of strings.
+    pub fn deserialize_inferred<V>(self, visitor: V) -> Result<V::Value, Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        match &*self.value {
+            "true" => return visitor.visit_bool(true),
+            "false" => return visitor.visit_bool(false),
+            s if is_number(s) => {
+                if let Ok(v) = s.parse() {
+                    return visitor.visit_u64(v);
+                }
+                if let Ok(v) = s.parse() {
+                    return visitor.visit_i64(v);
+                }
+                if let Ok(v) = s.parse() {
+                    return visitor.visit_f64(v);
+                }
+            }
+            _ => {}
+        }
+
+        de::Deserializer::deserialize_any(self, visitor)
+    }
+
+    /// The length of the percent-decoded bytes.
+    pub fn len(&self) -> usize {
+        match self.raw.as_deref() {
+            Some(bytes) => bytes.len(),
+            None => self.value.len(),
+        }
+    }
+}
+
+/// A byte span with 32-bit offsets, to keep parts small. Spans that do not
+/// fit, in input larger than 4 GiB, are not kept.
+#[derive(Clone, Copy, Debug)]
+struct Span {
+    start: u32,
+    end: u32,
+}
+
+impl Span {
+    const UNKNOWN: Span = Span { start: u32::MAX, end: 0 };
+
+    fn new(span: Range<usize>) -> Self {
+        match (u32::try_from(span.start), u32::try_from(span.end)) {
+            (Ok(start), Ok(end)) if start <= end => Span { start, end },
+            _ => Span::UNKNOWN,
+        }
+    }
+
+    fn get(self) -> Option<Range<usize>> {
+        (self.start <= self.end).then_some(self.start as usize..self.end as usize)
+    }
+}
+
+impl PartialEq for Part<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        self.value == other.value
+    }
+}
+
+impl Eq for Part<'_> {}
+
+impl PartialOrd for Part<'_> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
 
-#[derive(PartialEq, PartialOrd, Eq, Ord, Hash)]
-pub(super) struct Part<'de>(pub Cow<'de, str>);
+impl Ord for Part<'_> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.value.cmp(&other.value)
+    }
+}
+
+impl Hash for Part<'_> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.value.hash(state);
+    }
+}
 
-impl<'de> IntoDeserializer<'de> for Part<'de> {
+impl<'de> IntoDeserializer<'de, Error> for Part<'de> {
     type Deserializer = Self;
 
     fn into_deserializer(self) -> Self::Deserializer {
@@ -27,9 +176,9 @@ macro_rules! forward_parsed_value {
             fn $method<V>(self, visitor: V) -> Result<V::Value, Self::Error>
                 where V: de::Visitor<'de>
             {
-                match self.0.parse::<$ty>() {
+                match self.value.parse::<$ty>() {
                     Ok(val) => val.into_deserializer().$method(visitor),
-                    Err(e) => Err(de::Error::custom(e))
+                    Err(e) => Err(Error::new(ErrorKind::Parse, e))
                 }
             }
         )*
@@ -43,7 +192,7 @@ impl<'de> de::Deserializer<'de> for Part<'de> {
     where
         V: de::Visitor<'de>,
     {
-        match self.0 {
+        match self.value {
             Cow::Borrowed(value) => visitor.visit_borrowed_str(value),
             Cow::Owned(value) => visitor.visit_string(value),
         }
@@ -53,7 +202,7 @@ impl<'de> de::Deserializer<'de> for Part<'de> {
     where
         V: de::Visitor<'de>,
     {
-        if self.0.is_empty() {
+        if self.value.is_empty() {
             // Types for which to treat an empty `Part` as none in this method.
             //
             // FIXME: Change to a `const` once MSRV is raised to 1.91 or later.
@@ -129,13 +278,31 @@ impl<'de> de::Deserializer<'de> for Part<'de> {
         visitor.visit_seq(PartSeqAccess(Some(self)))
     }
 
+    fn deserialize_bytes<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        match self.raw {
+            Some(raw) => match *raw {
+                Cow::Borrowed(bytes) => visitor.visit_borrowed_bytes(bytes),
+                Cow::Owned(bytes) => visitor.visit_byte_buf(bytes),
+            },
+            None => self.deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_byte_buf<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        self.deserialize_bytes(visitor)
+    }
+
     forward_to_deserialize_any! {
         char
         str
         string
         unit
-        bytes
-        byte_buf
         unit_struct
         tuple_struct
         struct
@@ -168,7 +335,7 @@ impl<'de> de::EnumAccess<'de> for Part<'de> {
     where
         V: de::DeserializeSeed<'de>,
     {
-        let variant = seed.deserialize(self.0.into_deserializer())?;
+        let variant = seed.deserialize(self.value.into_deserializer())?;
         Ok((variant, UnitOnlyVariantAccess))
     }
 }
@@ -193,6 +360,40 @@ impl<'de> de::SeqAccess<'de> for PartSeqAccess<'de> {
     }
 }
 
+/// Whether `s` is a number in the JSON grammar, without leading zeros that
+/// would be lost for strings like ZIP codes.
+fn is_number(s: &str) -> bool {
+    fn digits(s: &[u8]) -> (usize, &[u8]) {
+        let len = s.iter().take_while(|b| b.is_ascii_digit()).count();
+        (len, &s[len..])
+    }
+
+    let s = s.strip_prefix('-').unwrap_or(s).as_bytes();
+    let rest = match digits(s) {
+        (0, _) => return false,
+        (len, _) if len > 1 && s[0] == b'0' => return false,
+        (_, rest) => rest,
+    };
+    let rest = match rest {
+        [b'.', rest @ ..] => match digits(rest) {
+            (0, _) => return false,
+            (_, rest) => rest,
+        },
+        _ => rest,
+    };
+    match rest {
+        [] => true,
+        [b'e' | b'E', rest @ ..] => {
+            let rest = match rest {
+                [b'+' | b'-', rest @ ..] => rest,
+                _ => rest,
+            };
+            matches!(digits(rest), (len, []) if len > 0)
+        }
+        _ => false,
+    }
+}
+
 pub(crate) struct UnitOnlyVariantAccess;
 
 impl<'de> de::VariantAccess<'de> for UnitOnlyVariantAccess {
@@ -206,14 +407,14 @@ impl<'de> de::VariantAccess<'de> for UnitOnlyVariantAccess {
     where
         T: de::DeserializeSeed<'de>,
     {
-        Err(Error::custom("expected unit variant"))
+        Err(Error::new(ErrorKind::Unsupported, "expected unit variant"))
     }
 
     fn tuple_variant<V>(self, _len: usize, _visitor: V) -> Result<V::Value, Self::Error>
     where
         V: de::Visitor<'de>,
     {
-        Err(Error::custom("expected unit variant"))
+        Err(Error::new(ErrorKind::Unsupported, "expected unit variant"))
     }
 
     fn struct_variant<V>(
@@ -224,6 +425,6 @@ impl<'de> de::VariantAccess<'de> for UnitOnlyVariantAccess {
     where
         V: de::Visitor<'de>,
     {
-        Err(Error::custom("expected unit variant"))
+        Err(Error::new(ErrorKind::Unsupported, "expected unit variant"))
     }
 }
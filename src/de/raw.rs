@@ -0,0 +1,326 @@
+use alloc::{borrow::Cow, vec::Vec};
+use core::{fmt, ops::Range, str};
+
+use super::{part::Part, Error, ErrorKind};
+
+/// An iterator over the pairs of a form as percent-decoded bytes, along with
+/// the byte span of each pair in the input.
+///
+/// This splits the input like `form_urlencoded::parse`, but does not decode
+/// the bytes as UTF-8. In strict mode, malformed input is reported as an error
+/// with the byte offset of the problem.
+#[derive(Clone, Copy)]
+pub(crate) struct RawPairs<'de> {
+    input: &'de [u8],
+    offset: usize,
+    strict: bool,
+    check_utf8: bool,
+}
+
+impl<'de> RawPairs<'de> {
+    pub fn new(input: &'de [u8]) -> Self {
+        RawPairs { input, offset: 0, strict: false, check_utf8: true }
+    }
+
+    /// Returns the input that has not been split into pairs yet.
+    pub fn remaining(&self) -> &'de [u8] {
+        self.input
+    }
+
+    /// Sets the offset of the input in the whole form, for error messages.
+    pub fn offset(mut self, offset: usize) -> Self {
+        self.offset = offset;
+        self
+    }
+
+    /// Rejects empty keys, malformed percent-escapes and, unless disabled with
+    /// [`check_utf8`][Self::check_utf8], invalid UTF-8.
+    pub fn strict(mut self, strict: bool) -> Self {
+        self.strict = strict;
+        self
+    }
+
+    /// Sets whether strict mode rejects invalid UTF-8, for input in another
+    /// encoding.
+    #[cfg(feature = "encoding")]
+    pub fn check_utf8(mut self, check_utf8: bool) -> Self {
+        self.check_utf8 = check_utf8;
+        self
+    }
+
+    fn decode(&self, input: &'de [u8], offset: usize) -> Result<Cow<'de, [u8]>, Error> {
+        if !self.strict {
+            return Ok(percent_decode(input));
+        }
+
+        check_escapes(input, offset)?;
+        let decoded = percent_decode(input);
+        if self.check_utf8 {
+            if let Err(e) = str::from_utf8(&decoded) {
+                let offset = offset + raw_len(input, e.valid_up_to());
+                return Err(syntax_error(format_args!("invalid UTF-8 at byte {}", offset)));
+            }
+        }
+        Ok(decoded)
+    }
+}
+
+impl<'de> Iterator for RawPairs<'de> {
+    type Item = Result<(Cow<'de, [u8]>, Cow<'de, [u8]>, Range<usize>), Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if self.input.is_empty() {
+                return None;
+            }
+
+            let offset = self.offset;
+            let (sequence, rest) = split_once(self.input, b'&');
+            self.offset += self.input.len() - rest.len();
+            self.input = rest;
+            if sequence.is_empty() {
+                continue;
+            }
+
+            let span = offset..offset + sequence.len();
+            let (key, value) = split_once(sequence, b'=');
+            if self.strict && key.is_empty() {
+                let error = syntax_error(format_args!("empty key at byte {}", offset));
+                return Some(Err(error.with_span(Some(span))));
+            }
+
+            let value_offset = offset + (sequence.len() - value.len());
+            let pair = match (self.decode(key, offset), self.decode(value, value_offset)) {
+                (Ok(key), Ok(value)) => Ok((key, value, span)),
+                (Err(e), _) | (_, Err(e)) => Err(e.with_span(Some(span))),
+            };
+            return Some(pair);
+        }
+    }
+}
+
+/// An iterator over the pairs of a form given as a string, like
+/// `form_urlencoded::parse`.
+///
+/// Unlike `RawPairs`, keys and values without escapes are borrowed from the
+/// input without checking them for UTF-8 again. This is used unless the
+/// deserializer is strict.
+#[derive(Clone, Copy)]
+pub(super) struct StrPairs<'de> {
+    input: &'de str,
+    offset: usize,
+}
+
+impl<'de> StrPairs<'de> {
+    pub fn new(input: &'de str) -> Self {
+        StrPairs { input, offset: 0 }
+    }
+
+    /// Returns the input that has not been split into pairs yet.
+    pub fn remaining(&self) -> &'de str {
+        self.input
+    }
+}
+
+impl<'de> Iterator for StrPairs<'de> {
+    type Item = (Cow<'de, str>, Part<'de>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let input = self.input.as_bytes();
+            if input.is_empty() {
+                return None;
+            }
+
+            // Find the end of the key and of the pair, and whether they have
+            // escapes, in one pass.
+            let mut key_end = None;
+            let mut escaped = false;
+            let mut end = input.len();
+            for (i, &b) in input.iter().enumerate() {
+                match b {
+                    b'&' => {
+                        end = i;
+                        break;
+                    }
+                    b'=' if key_end.is_none() => key_end = Some(i),
+                    b'%' | b'+' => escaped = true,
+                    _ => {}
+                }
+            }
+
+            let offset = self.offset;
+            let sequence = &self.input[..end];
+            self.input = self.input.get(end + 1..).unwrap_or_default();
+            self.offset += end + 1;
+            if sequence.is_empty() {
+                continue;
+            }
+
+            let span = offset..offset + end;
+            let (key, value) = match key_end {
+                Some(i) => (&sequence[..i], &sequence[i + 1..]),
+                None => (sequence, ""),
+            };
+            if !escaped {
+                let value = Part::new(Cow::Borrowed(value)).with_span(span);
+                return Some((Cow::Borrowed(key), value));
+            }
+            return Some((decode_str(key).value, decode_str(value).with_span(span)));
+        }
+    }
+}
+
+fn decode_str(input: &str) -> Part<'_> {
+    match percent_decode(input.as_bytes()) {
+        Cow::Borrowed(_) => Part::new(Cow::Borrowed(input)),
+        decoded => Part::from_bytes(decoded),
+    }
+}
+
+fn split_once(input: &[u8], separator: u8) -> (&[u8], &[u8]) {
+    match input.iter().position(|&b| b == separator) {
+        Some(pos) => (&input[..pos], &input[pos + 1..]),
+        None => (input, &[]),
+    }
+}
+
+/// Returns an error for the first percent-escape that is not followed by two
+/// hex digits.
+fn check_escapes(input: &[u8], offset: usize) -> Result<(), Error> {
+    for (i, _) in input.iter().enumerate().filter(|&(_, &b)| b == b'%') {
+        match input.get(i + 1..i + 3) {
+            Some(digits) if decode_hex(digits).is_some() => {}
+            Some(digits) => {
+                return Err(syntax_error(format_args!(
+                    "invalid percent-escape `%{}` at byte {}",
+                    digits.escape_ascii(),
+                    offset + i
+                )));
+            }
+            None => {
+                return Err(syntax_error(format_args!(
+                    "truncated percent-escape at byte {}",
+                    offset + i
+                )));
+            }
+        }
+    }
+    Ok(())
+}
+
+fn syntax_error(message: fmt::Arguments<'_>) -> Error {
+    Error::new(ErrorKind::Syntax, message)
+}
+
+/// Returns the number of bytes of `input` that decode to the first `len`
+/// bytes, for input without invalid escapes.
+fn raw_len(input: &[u8], len: usize) -> usize {
+    let mut pos = 0;
+    for _ in 0..len {
+        pos += if input[pos] == b'%' { 3 } else { 1 };
+    }
+    pos
+}
+
+/// Replaces `+` with spaces and decodes `%XX` escapes. Invalid escapes are
+/// kept as they are.
+fn percent_decode(input: &[u8]) -> Cow<'_, [u8]> {
+    if !input.iter().any(|&b| b == b'+' || b == b'%') {
+        return Cow::Borrowed(input);
+    }
+
+    let mut decoded = Vec::with_capacity(input.len());
+    let mut i = 0;
+    while i < input.len() {
+        match input[i] {
+            b'+' => decoded.push(b' '),
+            b'%' => match input.get(i + 1..i + 3).and_then(decode_hex) {
+                Some(byte) => {
+                    decoded.push(byte);
+                    i += 2;
+                }
+                None => decoded.push(b'%'),
+            },
+            b => decoded.push(b),
+        }
+        i += 1;
+    }
+
+    Cow::Owned(decoded)
+}
+
+fn decode_hex(digits: &[u8]) -> Option<u8> {
+    let hi = char::from(digits[0]).to_digit(16)?;
+    let lo = char::from(digits[1]).to_digit(16)?;
+    Some((hi * 16 + lo) as u8)
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::{borrow::Cow, string::ToString as _, vec::Vec};
+
+    use super::{RawPairs, StrPairs};
+
+    fn pairs(input: &str) -> Vec<(&[u8], &[u8])> {
+        RawPairs::new(input.as_bytes())
+            .map(|pair| match pair.unwrap() {
+                (Cow::Borrowed(key), Cow::Borrowed(value), _) => (key, value),
+                _ => panic!("pair was not borrowed"),
+            })
+            .collect()
+    }
+
+    fn strict_error(input: &[u8]) -> alloc::string::String {
+        RawPairs::new(input).strict(true).find_map(Result::err).unwrap().to_string()
+    }
+
+    #[test]
+    fn split() {
+        assert_eq!(
+            pairs("a=1&&b&=c&d=e=f"),
+            [(&b"a"[..], &b"1"[..]), (b"b", b""), (b"", b"c"), (b"d", b"e=f")]
+        );
+    }
+
+    #[test]
+    fn str_pairs() {
+        let pairs: Vec<_> = StrPairs::new("a=1&&b&=c&d=e=f&%C3%A9=x+y")
+            .map(|(key, value)| {
+                let span = value.span();
+                (key, value.value, span)
+            })
+            .collect();
+        assert_eq!(
+            pairs,
+            [
+                (Cow::Borrowed("a"), Cow::Borrowed("1"), Some(0..3)),
+                (Cow::Borrowed("b"), Cow::Borrowed(""), Some(5..6)),
+                (Cow::Borrowed(""), Cow::Borrowed("c"), Some(7..9)),
+                (Cow::Borrowed("d"), Cow::Borrowed("e=f"), Some(10..15)),
+                (Cow::Owned("é".into()), Cow::Owned("x y".into()), Some(16..26)),
+            ]
+        );
+        assert!(matches!(pairs[0], (Cow::Borrowed(_), Cow::Borrowed(_), _)));
+        assert!(matches!(pairs[4], (Cow::Owned(_), Cow::Owned(_), _)));
+    }
+
+    #[test]
+    fn percent_decode() {
+        assert_eq!(super::percent_decode(b"abc"), Cow::Borrowed(b"abc"));
+        assert_eq!(super::percent_decode(b"a+b%20c"), &b"a b c"[..]);
+        assert_eq!(super::percent_decode(b"%ff%FE"), &b"\xff\xfe"[..]);
+        assert_eq!(super::percent_decode(b"%zz%4%"), &b"%zz%4%"[..]);
+    }
+
+    #[test]
+    fn strict() {
+        assert!(RawPairs::new(b"a=%C3%A9&&b=+").strict(true).all(|pair| pair.is_ok()));
+        assert_eq!(strict_error(b"a=1&b=%zz"), "invalid percent-escape `%zz` at byte 6");
+        assert_eq!(strict_error(b"a=1&b%2=2"), "truncated percent-escape at byte 5");
+        assert_eq!(strict_error(b"a=1&b=%4"), "truncated percent-escape at byte 6");
+        assert_eq!(strict_error(b"a=%C3%A9%FFx"), "invalid UTF-8 at byte 8");
+        assert_eq!(strict_error(b"a=\xC3"), "invalid UTF-8 at byte 2");
+        assert_eq!(strict_error(b"a=1&=2"), "empty key at byte 4");
+    }
+}
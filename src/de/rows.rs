@@ -0,0 +1,164 @@
+use alloc::{borrow::Cow, string::String, vec, vec::Vec};
+use core::{fmt, marker::PhantomData};
+
+use serde_core::de::{self, value::MapAccessDeserializer, Deserialize, Error as _, Visitor};
+
+use super::{empty_as_none::CowStrVisitor, entries::Entries, part::Part, Error};
+
+pub(super) struct RowsVisitor<T> {
+    pub _phantom: PhantomData<T>,
+}
+
+impl<'de, T> Visitor<'de> for RowsVisitor<T>
+where
+    T: Deserialize<'de>,
+{
+    type Value = Vec<T>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        formatter.write_str("a map of repeated fields")
+    }
+
+    fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+    where
+        A: de::MapAccess<'de>,
+    {
+        let mut columns: Vec<(Cow<'de, str>, Vec<Cow<'de, str>>)> = Vec::new();
+        while let Some(CowStr(key)) = map.next_key()? {
+            let Column(values) = map.next_value()?;
+            if let Some((first_key, first_values)) = columns.first() {
+                if values.len() != first_values.len() {
+                    return Err(A::Error::custom(format_args!(
+                        "mismatched number of values: `{}` has {}, but `{}` has {}",
+                        first_key,
+                        first_values.len(),
+                        key,
+                        values.len(),
+                    )));
+                }
+            }
+
+            columns.push((key, values));
+        }
+
+        let len = columns.first().map_or(0, |(_, values)| values.len());
+        let mut columns: Vec<_> =
+            columns.into_iter().map(|(key, values)| (key, values.into_iter())).collect();
+
+        (0..len)
+            .map(|_| {
+                let row = columns.iter_mut().filter_map(|(key, values)| {
+                    Some((Part::new(key.clone()), Part::new(values.next()?)))
+                });
+                T::deserialize(MapAccessDeserializer::new(Entries::new(row)))
+                    .map_err(Error::into_error)
+            })
+            .collect()
+    }
+}
+
+struct CowStr<'de>(Cow<'de, str>);
+
+impl<'de> Deserialize<'de> for CowStr<'de> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        deserializer.deserialize_str(CowStrVisitor).map(CowStr)
+    }
+}
+
+/// The values of a single repeated field.
+struct Column<'de>(Vec<Cow<'de, str>>);
+
+impl<'de> Deserialize<'de> for Column<'de> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        deserializer.deserialize_any(ColumnVisitor)
+    }
+}
+
+struct ColumnVisitor;
+
+impl<'de> Visitor<'de> for ColumnVisitor {
+    type Value = Column<'de>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        formatter.write_str("a string or a sequence of strings")
+    }
+
+    fn visit_borrowed_str<E>(self, v: &'de str) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        CowStrVisitor.visit_borrowed_str(v).map(|v| Column(vec![v]))
+    }
+
+    fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        CowStrVisitor.visit_str(v).map(|v| Column(vec![v]))
+    }
+
+    fn visit_string<E>(self, v: String) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        CowStrVisitor.visit_string(v).map(|v| Column(vec![v]))
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+    where
+        A: de::SeqAccess<'de>,
+    {
+        let mut values = Vec::new();
+        while let Some(Cell(value)) = seq.next_element()? {
+            values.push(value);
+        }
+
+        Ok(Column(values))
+    }
+
+    fn visit_bool<E>(self, v: bool) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        CowStrVisitor.visit_bool(v).map(|v| Column(vec![v]))
+    }
+
+    fn visit_i64<E>(self, v: i64) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        CowStrVisitor.visit_i64(v).map(|v| Column(vec![v]))
+    }
+
+    fn visit_u64<E>(self, v: u64) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        CowStrVisitor.visit_u64(v).map(|v| Column(vec![v]))
+    }
+
+    fn visit_f64<E>(self, v: f64) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        CowStrVisitor.visit_f64(v).map(|v| Column(vec![v]))
+    }
+}
+
+/// A single value of a repeated field.
+struct Cell<'de>(Cow<'de, str>);
+
+impl<'de> Deserialize<'de> for Cell<'de> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        deserializer.deserialize_any(CowStrVisitor).map(Cell)
+    }
+}
@@ -0,0 +1,112 @@
+use core::{
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use futures_core::Stream;
+use futures_io::AsyncRead;
+use serde_core::de;
+
+use super::{Error, Parser};
+
+impl Parser {
+    /// Feeds all input from an [`AsyncRead`] to the parser.
+    ///
+    /// Returns an error as soon as the input is longer than the limit set with
+    /// [`max_input_len`][Self::max_input_len], without reading the rest of it.
+    pub async fn feed_async_read<R>(&mut self, mut reader: R) -> Result<(), Error>
+    where
+        R: AsyncRead + Unpin,
+    {
+        let mut buf = [0; 8 * 1024];
+        loop {
+            match (Read { reader: &mut reader, buf: &mut buf }).await {
+                Ok(0) => return Ok(()),
+                Ok(n) => self.feed(&buf[..n])?,
+                Err(e) if e.kind() == std::io::ErrorKind::Interrupted => {}
+                Err(e) => return Err(de::Error::custom(e)),
+            }
+        }
+    }
+
+    /// Feeds all chunks of a [`Stream`] to the parser.
+    ///
+    /// Returns an error as soon as the input is longer than the limit set with
+    /// [`max_input_len`][Self::max_input_len], without polling the rest of the
+    /// stream.
+    pub async fn feed_stream<S>(&mut self, mut stream: S) -> Result<(), Error>
+    where
+        S: Stream + Unpin,
+        S::Item: AsRef<[u8]>,
+    {
+        while let Some(chunk) = (Next { stream: &mut stream }).await {
+            self.feed(chunk.as_ref())?;
+        }
+        Ok(())
+    }
+}
+
+/// Deserializes a `application/x-www-form-urlencoded` value from an
+/// [`AsyncRead`].
+///
+/// Reading stops with an error once more than `max_input_len` bytes were
+/// read. Use [`Parser::feed_async_read`] for more control over parsing.
+///
+/// Readers from tokio can be used through the compatibility layer of
+/// `tokio-util`.
+pub async fn from_async_read<R, T>(reader: R, max_input_len: usize) -> Result<T, Error>
+where
+    R: AsyncRead + Unpin,
+    T: de::DeserializeOwned,
+{
+    let mut parser = Parser::new().max_input_len(max_input_len);
+    parser.feed_async_read(reader).await?;
+    T::deserialize(parser.finish())
+}
+
+/// Deserializes a `application/x-www-form-urlencoded` value from a [`Stream`]
+/// of byte chunks, like `Bytes`.
+///
+/// Polling stops with an error once more than `max_input_len` bytes were
+/// received. Use [`Parser::feed_stream`] for more control over parsing.
+///
+/// Streams of `Result`s can be converted to an [`AsyncRead`] for
+/// [`from_async_read`] with `TryStreamExt::into_async_read` from the `futures`
+/// crate.
+pub async fn from_stream<S, T>(stream: S, max_input_len: usize) -> Result<T, Error>
+where
+    S: Stream + Unpin,
+    S::Item: AsRef<[u8]>,
+    T: de::DeserializeOwned,
+{
+    let mut parser = Parser::new().max_input_len(max_input_len);
+    parser.feed_stream(stream).await?;
+    T::deserialize(parser.finish())
+}
+
+struct Read<'a, R> {
+    reader: &'a mut R,
+    buf: &'a mut [u8],
+}
+
+impl<R: AsyncRead + Unpin> Future for Read<'_, R> {
+    type Output = std::io::Result<usize>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.get_mut();
+        Pin::new(&mut *this.reader).poll_read(cx, this.buf)
+    }
+}
+
+struct Next<'a, S> {
+    stream: &'a mut S,
+}
+
+impl<S: Stream + Unpin> Future for Next<'_, S> {
+    type Output = Option<S::Item>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        Pin::new(&mut *self.get_mut().stream).poll_next(cx)
+    }
+}
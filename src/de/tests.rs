@@ -1,11 +1,28 @@
 use alloc::{
+    borrow::Cow,
+    boxed::Box,
     collections::{BTreeMap, BTreeSet},
+    format,
     string::{String, ToString as _},
     vec::Vec,
 };
+use core::{fmt, marker::PhantomData};
 
 use insta::{assert_compact_debug_snapshot, assert_snapshot};
-use serde::Deserialize;
+use serde::{de, Deserialize};
+
+use crate::{
+    de::{DuplicateKeys, Limits},
+    KeyNotation,
+};
+
+/// Deserializes `input` with a `Deserializer` that is configured by `f`.
+fn from_bytes<'de, T: Deserialize<'de>>(
+    input: &'de [u8],
+    f: impl FnOnce(super::Deserializer<'de>) -> super::Deserializer<'de>,
+) -> Result<T, super::Error> {
+    T::deserialize(f(super::Deserializer::from_bytes(input)))
+}
 
 #[derive(Deserialize, Debug, PartialEq)]
 struct NewType<T>(T);
@@ -27,7 +44,7 @@ fn deserialize_option_newtype_i32() {
     // attributes can be used to work arounds it.
     assert_compact_debug_snapshot!(
         super::from_str::<Vec<(String, Option<NewType<i32>>)>>("field="),
-        @r#"Err(Error("cannot parse integer from empty string"))"#
+        @r#"Err(Error { kind: Parse, message: "cannot parse integer from empty string", key: None, span: None })"#
     );
 }
 
@@ -292,6 +309,105 @@ fn deserialize_internally_tagged_enum() {
     );
 }
 
+#[test]
+fn deserialize_infer_scalars() {
+    #[allow(dead_code)]
+    #[derive(Debug, Deserialize)]
+    struct Search {
+        q: String,
+        #[serde(flatten)]
+        page: Page,
+    }
+
+    #[allow(dead_code)]
+    #[derive(Debug, Deserialize)]
+    struct Page {
+        page: u32,
+        exact: bool,
+        ids: Vec<i64>,
+    }
+
+    #[allow(dead_code)]
+    #[derive(Debug, Deserialize)]
+    #[serde(tag = "shape", rename_all = "lowercase")]
+    enum Shape {
+        Circle { radius: f64 },
+    }
+
+    #[allow(dead_code)]
+    #[derive(Debug, Deserialize)]
+    #[serde(untagged)]
+    enum Id {
+        Number { id: u64 },
+        Name { id: String },
+    }
+
+    let input = "q=pens&page=2&exact=true&ids=-1&ids=5";
+    assert_compact_debug_snapshot!(
+        super::from_str::<Search>(input).map_err(|e| e.to_string()),
+        @r#"Err("invalid type: string \"2\", expected u32")"#
+    );
+    assert_compact_debug_snapshot!(
+        from_bytes::<Search>(input.as_bytes(), |d| d.infer_scalars(true)),
+        @r#"Ok(Search { q: "pens", page: Page { page: 2, exact: true, ids: [-1, 5] } })"#
+    );
+    assert_compact_debug_snapshot!(
+        from_bytes::<Search>(b"q=pens&page=two&exact=true&ids=1", |d| d.infer_scalars(true))
+            .map_err(|e| e.to_string()),
+        @r#"Err("invalid type: string \"two\", expected u32")"#
+    );
+    assert_compact_debug_snapshot!(
+        from_bytes::<Shape>(b"shape=circle&radius=1.5e2", |d| d.infer_scalars(true)),
+        @"Ok(Circle { radius: 150.0 })"
+    );
+    assert_compact_debug_snapshot!(
+        from_bytes::<Id>(b"id=42", |d| d.infer_scalars(true)),
+        @"Ok(Number { id: 42 })"
+    );
+    assert_compact_debug_snapshot!(
+        from_bytes::<Id>(b"id=007", |d| d.infer_scalars(true)),
+        @r#"Ok(Name { id: "007" })"#
+    );
+    assert_compact_debug_snapshot!(
+        from_bytes::<Id>(b"id=1e", |d| d.infer_scalars(true)),
+        @r#"Ok(Name { id: "1e" })"#
+    );
+}
+
+#[test]
+fn deserialize_empty_as_none_infer_scalars() {
+    #[derive(Debug, Deserialize)]
+    #[allow(dead_code)]
+    struct Form {
+        q: String,
+        #[serde(flatten)]
+        filter: Filter,
+    }
+
+    #[derive(Debug, Deserialize)]
+    #[allow(dead_code)]
+    struct Filter {
+        #[serde(default, deserialize_with = "crate::de::empty_as_none")]
+        min: Option<String>,
+        #[serde(default, deserialize_with = "crate::de::empty_as_none")]
+        max: Option<u32>,
+    }
+
+    assert_compact_debug_snapshot!(
+        from_bytes::<Form>(b"q=x&min=1.50&max=10", |d| d.infer_scalars(true)),
+        @r#"Ok(Form { q: "x", filter: Filter { min: Some("1.5"), max: Some(10) } })"#
+    );
+    assert_compact_debug_snapshot!(
+        from_bytes::<Form>(b"q=x&min=true&max=", |d| d.infer_scalars(true)),
+        @r#"Ok(Form { q: "x", filter: Filter { min: Some("true"), max: None } })"#
+    );
+    assert_compact_debug_snapshot!(
+        from_bytes::<Form>(b"q=x&min=&max=-1", |d| d.infer_scalars(true))
+            .map_err(|e| e.to_string()),
+        @r#"Err("invalid digit found in string")"#
+    );
+}
+
 #[test]
 fn deserialize_optional_list_empty_value() {
     #[allow(dead_code)]
@@ -335,3 +451,1054 @@ fn deserialize_empty_btreeset_as_none() {
         @r#"Ok(Form { set: {None, Some("x")} })"#
     );
 }
+
+#[derive(Debug, Deserialize)]
+#[allow(dead_code)]
+struct User {
+    name: String,
+    email: String,
+}
+
+#[test]
+fn deserialize_nested_struct_brackets() {
+    #[allow(dead_code)]
+    #[derive(Debug, Deserialize)]
+    struct Form {
+        id: u32,
+        user: User,
+    }
+
+    assert_compact_debug_snapshot!(
+        from_bytes::<Form>(b"user[name]=a&id=1&user[email]=b", |d| {
+            d.key_notation(KeyNotation::Brackets)
+        }),
+        @r#"Ok(Form { id: 1, user: User { name: "a", email: "b" } })"#
+    );
+}
+
+#[test]
+fn deserialize_nested_map_brackets() {
+    #[allow(dead_code)]
+    #[derive(Debug, Deserialize)]
+    struct Form {
+        filters: BTreeMap<String, String>,
+        tags: Vec<String>,
+    }
+
+    assert_compact_debug_snapshot!(
+        from_bytes::<Form>(b"filters[status]=open&tags[]=a&filters[owner]=me&tags[]=b", |d| {
+            d.key_notation(KeyNotation::Brackets)
+        }),
+        @r#"Ok(Form { filters: {"owner": "me", "status": "open"}, tags: ["a", "b"] })"#
+    );
+}
+
+#[test]
+fn deserialize_deeply_nested_brackets() {
+    assert_compact_debug_snapshot!(
+        from_bytes::<BTreeMap<String, BTreeMap<String, BTreeMap<String, Option<i32>>>>>(
+            b"a[b][c]=1&a[b][d]=&a%5Bx%5D[y]=2",
+            |d| d.key_notation(KeyNotation::Brackets),
+        ),
+        @r#"Ok({"a": {"b": {"c": Some(1), "d": None}, "x": {"y": Some(2)}}})"#
+    );
+}
+
+#[test]
+fn deserialize_malformed_brackets_as_flat() {
+    assert_compact_debug_snapshot!(
+        from_bytes::<BTreeMap<String, String>>(b"a[b=1&[c]=2&d[e]f=3", |d| {
+            d.key_notation(KeyNotation::Brackets)
+        }),
+        @r#"Ok({"[c]": "2", "a[b": "1", "d[e]f": "3"})"#
+    );
+}
+
+#[test]
+fn deserialize_conflicting_nested_keys() {
+    assert_snapshot!(
+        from_bytes::<BTreeMap<String, BTreeMap<String, String>>>(b"user=x&user[name]=y", |d| {
+            d.key_notation(KeyNotation::Brackets)
+        })
+        .unwrap_err(),
+        @"key `user[name]` conflicts with a previous key"
+    );
+}
+
+#[test]
+fn deserialize_flat_keys_by_default() {
+    assert_compact_debug_snapshot!(
+        super::from_str::<BTreeMap<String, String>>("user[name]=a&user[email]=b"),
+        @r#"Ok({"user[email]": "b", "user[name]": "a"})"#
+    );
+}
+
+#[test]
+fn deserialize_nested_struct_dots() {
+    #[allow(dead_code)]
+    #[derive(Debug, Deserialize)]
+    struct Form {
+        user: User,
+        filters: BTreeMap<String, String>,
+    }
+
+    assert_compact_debug_snapshot!(
+        from_bytes::<Form>(b"user.name=a&filters.status=open&user.email=b", |d| {
+            d.key_notation(KeyNotation::Dots)
+        }),
+        @r#"Ok(Form { user: User { name: "a", email: "b" }, filters: {"status": "open"} })"#
+    );
+}
+
+#[test]
+fn deserialize_image_button_dots() {
+    #[allow(dead_code)]
+    #[derive(Debug, Deserialize)]
+    struct Point {
+        x: u32,
+        y: u32,
+    }
+
+    #[allow(dead_code)]
+    #[derive(Debug, Deserialize)]
+    struct Form {
+        submit: Point,
+    }
+
+    assert_compact_debug_snapshot!(
+        from_bytes::<Form>(b"submit.x=10&submit.y=20", |d| d.key_notation(KeyNotation::Dots)),
+        @"Ok(Form { submit: Point { x: 10, y: 20 } })"
+    );
+}
+
+#[test]
+fn deserialize_malformed_dots_as_flat() {
+    assert_compact_debug_snapshot!(
+        from_bytes::<BTreeMap<String, String>>(b".a=1&b.=2&c..d=3&e[f]g=4", |d| {
+            d.key_notation(KeyNotation::Dots)
+        }),
+        @r#"Ok({".a": "1", "b.": "2", "c..d": "3", "e[f]g": "4"})"#
+    );
+}
+
+#[derive(Debug, Deserialize)]
+#[allow(dead_code)]
+struct LineItem {
+    sku: String,
+    qty: u32,
+}
+
+#[test]
+fn deserialize_indexed_structs_brackets() {
+    #[allow(dead_code)]
+    #[derive(Debug, Deserialize)]
+    struct Form {
+        items: Vec<LineItem>,
+    }
+
+    assert_compact_debug_snapshot!(
+        from_bytes::<Form>(
+            b"items[0][sku]=A&items[0][qty]=2&items[1][sku]=B&items[1][qty]=1",
+            |d| d.key_notation(KeyNotation::Brackets),
+        ),
+        @r#"Ok(Form { items: [LineItem { sku: "A", qty: 2 }, LineItem { sku: "B", qty: 1 }] })"#
+    );
+}
+
+#[test]
+fn deserialize_indexed_structs_dots() {
+    #[allow(dead_code)]
+    #[derive(Debug, Deserialize)]
+    struct Form {
+        items: Vec<LineItem>,
+    }
+
+    assert_compact_debug_snapshot!(
+        from_bytes::<Form>(b"items[0].sku=A&items[0].qty=2&items.1.sku=B&items.1.qty=1", |d| {
+            d.key_notation(KeyNotation::Dots)
+        }),
+        @r#"Ok(Form { items: [LineItem { sku: "A", qty: 2 }, LineItem { sku: "B", qty: 1 }] })"#
+    );
+}
+
+#[test]
+fn deserialize_sparse_indices() {
+    assert_compact_debug_snapshot!(
+        from_bytes::<BTreeMap<String, Vec<i32>>>(b"a[10]=3&a[2]=2&a[0]=1", |d| {
+            d.key_notation(KeyNotation::Brackets)
+        }),
+        @r#"Ok({"a": [1, 2, 3]})"#
+    );
+}
+
+#[test]
+fn deserialize_nested_sequences() {
+    assert_compact_debug_snapshot!(
+        from_bytes::<BTreeMap<String, Vec<Vec<i32>>>>(b"a[0]=1&a[1]=2&a[0]=3", |d| {
+            d.key_notation(KeyNotation::Brackets)
+        }),
+        @r#"Ok({"a": [[1, 3], [2]]})"#
+    );
+}
+
+#[test]
+fn deserialize_invalid_indices() {
+    assert_snapshot!(
+        from_bytes::<BTreeMap<String, Vec<i32>>>(b"a[0]=1&a[x]=2", |d| {
+            d.key_notation(KeyNotation::Brackets)
+        })
+        .unwrap_err(),
+        @"invalid sequence index `x`"
+    );
+    assert_snapshot!(
+        from_bytes::<BTreeMap<String, Vec<i32>>>(b"a[1001]=1", |d| {
+            d.key_notation(KeyNotation::Brackets)
+        })
+        .unwrap_err(),
+        @"limit exceeded: sequence index 1001 is larger than the maximum of 1000"
+    );
+}
+
+#[derive(Debug, Deserialize)]
+#[allow(dead_code)]
+struct Person {
+    name: String,
+    age: u8,
+}
+
+#[test]
+fn deserialize_rows() {
+    #[allow(dead_code)]
+    #[derive(Debug, Deserialize)]
+    struct Form {
+        page: u32,
+        #[serde(flatten, deserialize_with = "crate::de::rows")]
+        people: Vec<Person>,
+    }
+
+    assert_compact_debug_snapshot!(
+        super::from_str::<Form>("page=1&name=a&age=1&name=b&age=2"),
+        @r#"Ok(Form { page: 1, people: [Person { name: "a", age: 1 }, Person { name: "b", age: 2 }] })"#
+    );
+    assert_compact_debug_snapshot!(
+        super::from_str::<Form>("page=1&name=a&age=1"),
+        @r#"Ok(Form { page: 1, people: [Person { name: "a", age: 1 }] })"#
+    );
+    assert_compact_debug_snapshot!(
+        super::from_str::<Form>("page=1"),
+        @"Ok(Form { page: 1, people: [] })"
+    );
+}
+
+#[test]
+fn deserialize_rows_infer_scalars() {
+    #[allow(dead_code)]
+    #[derive(Debug, Deserialize)]
+    struct Form {
+        #[serde(flatten, deserialize_with = "crate::de::rows")]
+        people: Vec<Guest>,
+    }
+
+    #[allow(dead_code)]
+    #[derive(Debug, Deserialize)]
+    struct Guest {
+        name: String,
+        age: Option<u8>,
+    }
+
+    let input = b"name=123&age=1&name=true&age=";
+    assert_compact_debug_snapshot!(
+        from_bytes::<Form>(input, |d| d.infer_scalars(true)),
+        @r#"Ok(Form { people: [Guest { name: "123", age: Some(1) }, Guest { name: "true", age: None }] })"#
+    );
+    assert_compact_debug_snapshot!(
+        from_bytes::<Form>(b"name=1.50&age=2", |d| d.infer_scalars(true)),
+        @r#"Ok(Form { people: [Guest { name: "1.5", age: Some(2) }] })"#
+    );
+}
+
+#[test]
+fn deserialize_rows_mismatched_lengths() {
+    #[allow(dead_code)]
+    #[derive(Debug, Deserialize)]
+    struct Form {
+        #[serde(flatten, deserialize_with = "crate::de::rows")]
+        people: Vec<Person>,
+    }
+
+    assert_snapshot!(
+        super::from_str::<Form>("name=a&age=1&name=b").unwrap_err(),
+        @"mismatched number of values: `name` has 2, but `age` has 1"
+    );
+}
+
+#[test]
+fn deserialize_rows_invalid_value() {
+    #[allow(dead_code)]
+    #[derive(Debug, Deserialize)]
+    struct Form {
+        #[serde(flatten, deserialize_with = "crate::de::rows")]
+        people: Vec<Person>,
+    }
+
+    let error = super::from_str::<Form>("name=a&age=x").unwrap_err();
+    assert_snapshot!(error, @"invalid digit found in string");
+    assert_compact_debug_snapshot!((error.kind(), error.key()), @r#"(Parse, Some("age"))"#);
+}
+
+#[derive(Debug, Deserialize)]
+#[allow(dead_code)]
+struct Subscription {
+    subscribe: bool,
+    tags: Vec<String>,
+}
+
+#[test]
+fn deserialize_duplicate_keys_error() {
+    assert_snapshot!(
+        super::from_str::<Subscription>("subscribe=false&subscribe=true&tags=a").unwrap_err(),
+        @"duplicate key `subscribe`"
+    );
+    assert_snapshot!(
+        super::from_str::<BTreeMap<String, Option<u32>>>("a%20b=1&a+b=2").unwrap_err(),
+        @"duplicate key `a b`"
+    );
+}
+
+#[test]
+fn deserialize_duplicate_keys_first() {
+    assert_compact_debug_snapshot!(
+        from_bytes::<Subscription>(b"subscribe=false&tags=a&subscribe=true&tags=b", |d| {
+            d.duplicate_keys(DuplicateKeys::First)
+        }),
+        @r#"Ok(Subscription { subscribe: false, tags: ["a", "b"] })"#
+    );
+}
+
+#[test]
+fn deserialize_duplicate_keys_last() {
+    assert_compact_debug_snapshot!(
+        from_bytes::<Subscription>(b"subscribe=false&tags=a&subscribe=true&tags=b", |d| {
+            d.duplicate_keys(DuplicateKeys::Last)
+        }),
+        @r#"Ok(Subscription { subscribe: true, tags: ["a", "b"] })"#
+    );
+}
+
+#[test]
+fn deserialize_duplicate_nested_keys() {
+    assert_snapshot!(
+        from_bytes::<BTreeMap<String, User>>(b"u[name]=a&u[email]=b&u[name]=c", |d| {
+            d.key_notation(KeyNotation::Brackets)
+        })
+        .unwrap_err(),
+        @"duplicate key `u[name]`"
+    );
+    assert_compact_debug_snapshot!(
+        BTreeMap::<String, User>::deserialize(
+            super::Deserializer::from_bytes(b"u[name]=a&u[email]=b&u[name]=c")
+                .key_notation(KeyNotation::Brackets)
+                .duplicate_keys(DuplicateKeys::Last)
+        ),
+        @r#"Ok({"u": User { name: "c", email: "b" }})"#
+    );
+}
+
+#[test]
+fn deserialize_struct_fields() {
+    #[allow(dead_code)]
+    #[derive(Debug, Deserialize)]
+    struct Form {
+        a: u8,
+        tags: Vec<String>,
+        #[serde(default)]
+        c: Option<bool>,
+    }
+
+    assert_compact_debug_snapshot!(
+        super::from_str::<Form>("tags=x&other=1&a=1&tags=y&other=2&tags=z"),
+        @r#"Ok(Form { a: 1, tags: ["x", "y", "z"], c: None })"#
+    );
+    assert_compact_debug_snapshot!(
+        super::from_str::<Form>("a=1&b=1&b=2&b=3&b=4&b=5&b=6&b=7&b=8&b=9&tags=x&c=true"),
+        @r#"Ok(Form { a: 1, tags: ["x"], c: Some(true) })"#
+    );
+    assert_compact_debug_snapshot!(
+        super::from_str::<Form>("c=true&a=1&tags=x&a=2"),
+        @r#"Err(Error { kind: Duplicate, message: "duplicate key `a`", key: Some("a"), span: Some(18..21) })"#
+    );
+    assert_compact_debug_snapshot!(
+        from_bytes::<Form>(b"c=true&a=1&tags=x&a=2", |d| d.duplicate_keys(DuplicateKeys::Last)),
+        @r#"Ok(Form { a: 2, tags: ["x"], c: Some(true) })"#
+    );
+    assert_snapshot!(
+        from_bytes::<Form>(b"a=1&tags=x&tags=y&tags=z", |d| {
+            d.limits(Limits::new().max_values_per_key(2))
+        })
+        .unwrap_err(),
+        @"limit exceeded: `tags` has more than 2 values"
+    );
+}
+
+/// A struct with the given fields, deserialized into its entries in the
+/// order of the input, for structs too wide to derive.
+struct WideStruct<V>(&'static [&'static str], PhantomData<V>);
+
+impl<'de, V: Deserialize<'de>> de::DeserializeSeed<'de> for WideStruct<V> {
+    type Value = Vec<(String, V)>;
+
+    fn deserialize<D: de::Deserializer<'de>>(
+        self,
+        deserializer: D,
+    ) -> Result<Self::Value, D::Error> {
+        deserializer.deserialize_struct("WideStruct", self.0, self)
+    }
+}
+
+impl<'de, V: Deserialize<'de>> de::Visitor<'de> for WideStruct<V> {
+    type Value = Vec<(String, V)>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        formatter.write_str("a struct")
+    }
+
+    fn visit_map<A: de::MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+        let mut entries = Vec::new();
+        while let Some(entry) = map.next_entry()? {
+            entries.push(entry);
+        }
+        Ok(entries)
+    }
+}
+
+/// Deserializes `input` as a struct with the fields `f0`, `f1` and so on,
+/// from bytes and from a string, which take different paths.
+fn from_str_wide<'de, V: Deserialize<'de> + PartialEq + fmt::Debug>(
+    fields: usize,
+    input: &'de str,
+) -> Result<Vec<(String, V)>, super::Error> {
+    let fields: Vec<&'static str> =
+        (0..fields).map(|i| &*Box::leak(format!("f{}", i).into_boxed_str())).collect();
+    let fields = &*Box::leak(fields.into_boxed_slice());
+
+    let seed = WideStruct(fields, PhantomData);
+    let result =
+        de::DeserializeSeed::deserialize(seed, super::Deserializer::from_bytes(input.as_bytes()));
+    let seed = WideStruct(fields, PhantomData);
+    let deserializer = super::Deserializer::with_input(super::Input::Str(input));
+    assert_eq!(de::DeserializeSeed::deserialize(seed, deserializer), result);
+    result
+}
+
+#[test]
+fn deserialize_wide_struct() {
+    // More fields than are grouped on the stack.
+    assert_compact_debug_snapshot!(
+        from_str_wide::<String>(20, "f17=a&f2=b&x=c"),
+        @r#"Ok([("f17", "a"), ("f2", "b"), ("x", "c")])"#
+    );
+    assert_compact_debug_snapshot!(
+        from_str_wide::<String>(20, "f17=a&f2=b&f17=c"),
+        @r#"Err(Error { kind: Duplicate, message: "duplicate key `f17`", key: Some("f17"), span: Some(11..16) })"#
+    );
+    assert_compact_debug_snapshot!(
+        from_str_wide::<Vec<String>>(20, "f17=a&f2=b&x=c&f17=d&x=e"),
+        @r#"Ok([("f17", ["a", "d"]), ("f2", ["b"]), ("x", ["c", "e"])])"#
+    );
+
+    // More fields than are tracked by their index.
+    assert_compact_debug_snapshot!(
+        from_str_wide::<String>(70, "f20=a&f66=b&f3=c&f67=d"),
+        @r#"Ok([("f20", "a"), ("f66", "b"), ("f3", "c"), ("f67", "d")])"#
+    );
+    assert_compact_debug_snapshot!(
+        from_str_wide::<String>(70, "f66=a&f3=b&f66=c"),
+        @r#"Err(Error { kind: Duplicate, message: "duplicate key `f66`", key: Some("f66"), span: Some(11..16) })"#
+    );
+    assert_compact_debug_snapshot!(
+        from_str_wide::<Vec<String>>(70, "f66=a&f20=b&f66=c&f20=d"),
+        @r#"Ok([("f66", ["a", "c"]), ("f20", ["b", "d"])])"#
+    );
+}
+
+#[test]
+fn deserialize_many_keys() {
+    let keys = |n: usize| (0..n).map(|i| format!("k{}={}", i, i)).collect::<Vec<_>>().join("&");
+
+    // Up to 32 other keys are compared with each other, beyond that they are
+    // grouped in a map.
+    for n in [31, 32, 33, 40] {
+        let input = keys(n);
+        assert_eq!(super::from_str::<BTreeMap<String, u32>>(&input).map(|map| map.len()), Ok(n));
+        assert_eq!(from_str_wide::<u32>(2, &input).map(|entries| entries.len()), Ok(n));
+
+        let input = format!("{}&k0=0", input);
+        let error = super::from_str::<BTreeMap<String, u32>>(&input).unwrap_err();
+        assert_eq!(error.to_string(), "duplicate key `k0`");
+        let error = from_str_wide::<u32>(2, &input).unwrap_err();
+        assert_eq!(error.to_string(), "duplicate key `k0`");
+    }
+
+    let input = format!("{}&{}", keys(40), keys(40));
+    assert_compact_debug_snapshot!(
+        from_str_wide::<Vec<u32>>(2, &input).map(|entries| entries[39].clone()),
+        @r#"Ok(("k39", [39, 39]))"#
+    );
+}
+
+#[test]
+fn deserialize_checkbox() {
+    #[derive(Debug, Deserialize)]
+    #[allow(dead_code)]
+    struct Form {
+        #[serde(default, deserialize_with = "crate::de::checkbox")]
+        agree: bool,
+    }
+
+    assert_compact_debug_snapshot!(super::from_str::<Form>("agree=on"), @"Ok(Form { agree: true })");
+    assert_compact_debug_snapshot!(super::from_str::<Form>("agree=YES"), @"Ok(Form { agree: true })");
+    assert_compact_debug_snapshot!(super::from_str::<Form>("agree=off"), @"Ok(Form { agree: false })");
+    assert_compact_debug_snapshot!(super::from_str::<Form>("agree="), @"Ok(Form { agree: false })");
+    assert_compact_debug_snapshot!(super::from_str::<Form>(""), @"Ok(Form { agree: false })");
+    assert_compact_debug_snapshot!(
+        super::from_str::<Form>("agree=0&agree=1"),
+        @"Ok(Form { agree: true })"
+    );
+    assert_compact_debug_snapshot!(super::from_str::<Form>("agree=0"), @"Ok(Form { agree: false })");
+    assert_snapshot!(
+        super::from_str::<Form>("agree=maybe").unwrap_err(),
+        @r#"invalid value: string "maybe", expected a checkbox value"#
+    );
+}
+
+#[test]
+fn deserialize_checkbox_infer_scalars() {
+    #[derive(Debug, Deserialize)]
+    #[allow(dead_code)]
+    struct Form {
+        q: String,
+        #[serde(flatten)]
+        consent: Consent,
+    }
+
+    #[derive(Debug, Deserialize)]
+    #[allow(dead_code)]
+    struct Consent {
+        #[serde(default, deserialize_with = "crate::de::checkbox")]
+        agree: bool,
+    }
+
+    assert_compact_debug_snapshot!(
+        from_bytes::<Form>(b"q=x&agree=1", |d| d.infer_scalars(true)),
+        @r#"Ok(Form { q: "x", consent: Consent { agree: true } })"#
+    );
+    assert_compact_debug_snapshot!(
+        from_bytes::<Form>(b"q=x&agree=true", |d| d.infer_scalars(true)),
+        @r#"Ok(Form { q: "x", consent: Consent { agree: true } })"#
+    );
+    assert_compact_debug_snapshot!(
+        from_bytes::<Form>(b"q=x&agree=0&agree=on", |d| d.infer_scalars(true)),
+        @r#"Ok(Form { q: "x", consent: Consent { agree: true } })"#
+    );
+    assert_compact_debug_snapshot!(
+        from_bytes::<Form>(b"q=x&agree=1&agree=0", |d| d.infer_scalars(true)),
+        @r#"Ok(Form { q: "x", consent: Consent { agree: false } })"#
+    );
+    assert_snapshot!(
+        from_bytes::<Form>(b"q=x&agree=2", |d| d.infer_scalars(true)).unwrap_err(),
+        @r#"invalid value: string "2", expected a checkbox value"#
+    );
+}
+
+#[test]
+fn deserialize_nested_checkbox() {
+    #[derive(Debug, Deserialize)]
+    #[allow(dead_code)]
+    struct Settings {
+        #[serde(default, deserialize_with = "crate::de::checkbox")]
+        newsletter: bool,
+    }
+
+    #[derive(Debug, Deserialize)]
+    #[allow(dead_code)]
+    struct Form {
+        settings: Settings,
+    }
+
+    assert_compact_debug_snapshot!(
+        from_bytes::<Form>(b"settings[newsletter]=0&settings[newsletter]=1", |d| {
+            d.key_notation(KeyNotation::Brackets)
+        }),
+        @"Ok(Form { settings: Settings { newsletter: true } })"
+    );
+}
+
+#[test]
+fn deserialize_fed_byte_by_byte() {
+    let input = b"cheese=comt%C3%A9&meat=ham&cheese=brie&empty=&last";
+    let mut parser = crate::de::Parser::new();
+    for byte in input {
+        parser.feed(core::slice::from_ref(byte)).unwrap();
+    }
+
+    let result = Vec::<(String, String)>::deserialize(parser.finish());
+    assert_eq!(result, super::from_bytes::<Vec<(String, String)>>(input));
+    assert_compact_debug_snapshot!(
+        result,
+        @r#"Ok([("cheese", "comté"), ("meat", "ham"), ("cheese", "brie"), ("empty", ""), ("last", "")])"#
+    );
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn deserialize_from_reader() {
+    /// A reader that returns at most `n` bytes per read.
+    struct Chunked<'a> {
+        input: &'a [u8],
+        n: usize,
+    }
+
+    impl std::io::Read for Chunked<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            let len = self.n.min(buf.len()).min(self.input.len());
+            buf[..len].copy_from_slice(&self.input[..len]);
+            self.input = &self.input[len..];
+            Ok(len)
+        }
+    }
+
+    #[derive(Debug, Deserialize)]
+    #[allow(dead_code)]
+    struct Form {
+        cheese: String,
+        tags: Vec<String>,
+        count: u32,
+    }
+
+    let input = b"cheese=comt%C3%A9&tags=a+b&tags=%26&count=12";
+    for n in 1..input.len() {
+        let form: Form = super::from_reader(Chunked { input, n }).unwrap();
+        assert_eq!(form.cheese, "comté");
+        assert_eq!(form.tags, ["a b", "&"]);
+        assert_eq!(form.count, 12);
+    }
+}
+
+#[cfg(feature = "async")]
+#[test]
+fn deserialize_from_stream() {
+    use futures::{executor::block_on, io::Cursor, stream};
+
+    #[derive(Debug, Deserialize)]
+    #[allow(dead_code)]
+    struct Form {
+        cheese: String,
+        tags: Vec<String>,
+    }
+
+    let chunks = ["cheese=comt%C", "3%A9&ta", "gs=a&tags=b"];
+    assert_compact_debug_snapshot!(
+        block_on(super::from_stream::<_, Form>(stream::iter(chunks), 64)),
+        @r#"Ok(Form { cheese: "comté", tags: ["a", "b"] })"#
+    );
+    assert_snapshot!(
+        block_on(super::from_stream::<_, Form>(stream::iter(chunks), 16)).unwrap_err(),
+        @"limit exceeded: input is longer than 16 bytes"
+    );
+
+    let input = Cursor::new(chunks.concat());
+    assert_compact_debug_snapshot!(
+        block_on(super::from_async_read::<_, Form>(input, 64)),
+        @r#"Ok(Form { cheese: "comté", tags: ["a", "b"] })"#
+    );
+}
+
+#[test]
+fn deserialize_limits() {
+    type Map = BTreeMap<String, Vec<String>>;
+    type Pairs = Vec<(String, String)>;
+
+    let limits = Limits::new().max_pairs(2);
+    assert_compact_debug_snapshot!(
+        from_bytes::<Map>(b"a=1&b=2", |d| d.limits(limits)),
+        @r#"Ok({"a": ["1"], "b": ["2"]})"#
+    );
+    assert_snapshot!(
+        from_bytes::<Map>(b"a=1&b=2&c=3", |d| d.limits(limits)).unwrap_err(),
+        @"limit exceeded: more than 2 pairs"
+    );
+    assert_snapshot!(
+        from_bytes::<Pairs>(b"a=1&b=2&c=3", |d| d.limits(limits)).unwrap_err(),
+        @"limit exceeded: more than 2 pairs"
+    );
+
+    assert_snapshot!(
+        from_bytes::<Map>(b"a=1&%61%62%63=2", |d| d.limits(Limits::new().max_key_len(2)))
+            .unwrap_err(),
+        @"limit exceeded: key is longer than 2 bytes"
+    );
+    assert_compact_debug_snapshot!(
+        from_bytes::<Map>(b"a=%C3%A9", |d| d.limits(Limits::new().max_value_len(2))),
+        @r#"Ok({"a": ["é"]})"#
+    );
+    assert_snapshot!(
+        from_bytes::<Map>(b"a=%C3%A9!", |d| d.limits(Limits::new().max_value_len(2))).unwrap_err(),
+        @"limit exceeded: value of `a` is longer than 2 bytes"
+    );
+    assert_snapshot!(
+        from_bytes::<Map>(b"a=1&a=2&a=3", |d| d.limits(Limits::new().max_values_per_key(2)))
+            .unwrap_err(),
+        @"limit exceeded: `a` has more than 2 values"
+    );
+    assert_snapshot!(
+        from_bytes::<Map>(b"ab=1&cd=2", |d| d.limits(Limits::new().max_total_len(5))).unwrap_err(),
+        @"limit exceeded: keys and values are longer than 5 bytes in total"
+    );
+}
+
+#[test]
+fn deserialize_nested_limits() {
+    type Map = BTreeMap<String, BTreeMap<String, BTreeMap<String, String>>>;
+    assert_compact_debug_snapshot!(
+        from_bytes::<Map>(b"a[b][c]=1", |d| {
+            d.key_notation(KeyNotation::Brackets).limits(Limits::new().max_depth(3))
+        }),
+        @r#"Ok({"a": {"b": {"c": "1"}}})"#
+    );
+    assert_snapshot!(
+        from_bytes::<Map>(b"a[b][c]=1", |d| {
+            d.key_notation(KeyNotation::Brackets).limits(Limits::new().max_depth(2))
+        })
+        .unwrap_err(),
+        @"limit exceeded: `a[b][c]` is nested deeper than the limit of 2"
+    );
+    assert_snapshot!(
+        from_bytes::<BTreeMap<String, Vec<String>>>(b"a[b][]=1&a[b][]=2", |d| {
+            d.key_notation(KeyNotation::Brackets).limits(Limits::new().max_values_per_key(1))
+        })
+        .unwrap_err(),
+        @"limit exceeded: `a[b][]` has more than 1 values"
+    );
+
+    type Lists = BTreeMap<String, Vec<String>>;
+    assert_compact_debug_snapshot!(
+        from_bytes::<Lists>(b"a[0]=x&a[5000]=y", |d| {
+            d.key_notation(KeyNotation::Brackets).limits(Limits::new().max_index(5000))
+        }),
+        @r#"Ok({"a": ["x", "y"]})"#
+    );
+    assert_snapshot!(
+        from_bytes::<Lists>(b"a[0]=x&a[11]=y", |d| {
+            d.key_notation(KeyNotation::Brackets).limits(Limits::new().max_index(10))
+        })
+        .unwrap_err(),
+        @"limit exceeded: sequence index 11 is larger than the maximum of 10"
+    );
+}
+
+#[test]
+fn feed_limits() {
+    let mut parser = crate::de::Parser::new().limits(Limits::new().max_pairs(2));
+    parser.feed(b"a=1&b=2&c").unwrap();
+    assert_snapshot!(parser.feed(b"=3&d=4").unwrap_err(), @"limit exceeded: more than 2 pairs");
+
+    let mut parser = crate::de::Parser::new().limits(Limits::new().max_pairs(2));
+    parser.feed(b"a=1&b=2&c=3").unwrap();
+    assert_snapshot!(
+        Vec::<(String, String)>::deserialize(parser.finish()).unwrap_err(),
+        @"limit exceeded: more than 2 pairs"
+    );
+}
+
+#[test]
+fn deserialize_byte_fields() {
+    #[derive(Deserialize, Debug)]
+    #[allow(dead_code)]
+    struct Webhook {
+        id: String,
+        signature: serde_bytes::ByteBuf,
+        tokens: Vec<serde_bytes::ByteBuf>,
+    }
+
+    let input = b"id=%FFx&signature=%FF%00%C3%A9+a&tokens=ab&tokens=%80";
+    assert_compact_debug_snapshot!(
+        super::from_bytes::<Webhook>(input),
+        @r#"Ok(Webhook { id: "�x", signature: [255, 0, 195, 169, 32, 97], tokens: [[97, 98], [128]] })"#
+    );
+
+    let mut parser = super::Parser::new();
+    parser.feed(b"signature=%FF%0").unwrap();
+    parser.feed(b"0&id=1&tokens=%").unwrap();
+    parser.feed(b"FE").unwrap();
+    assert_compact_debug_snapshot!(
+        Webhook::deserialize(parser.finish()),
+        @r#"Ok(Webhook { id: "1", signature: [255, 0], tokens: [[254]] })"#
+    );
+}
+
+#[test]
+fn deserialize_error_details() {
+    #[derive(Deserialize, Debug)]
+    #[allow(dead_code)]
+    struct Item {
+        name: String,
+        price: u32,
+        #[serde(default)]
+        tags: Vec<u8>,
+    }
+
+    #[derive(Deserialize, Debug)]
+    #[allow(dead_code)]
+    struct Order {
+        items: Vec<Item>,
+    }
+
+    assert_compact_debug_snapshot!(
+        super::from_str::<Item>("name=Pen&price=abc").unwrap_err(),
+        @r#"Error { kind: Parse, message: "invalid digit found in string", key: Some("price"), span: Some(9..18) }"#
+    );
+    assert_compact_debug_snapshot!(
+        super::from_str::<Item>("name=Pen&price=1&tags=1&tags=x").unwrap_err(),
+        @r#"Error { kind: Parse, message: "invalid digit found in string", key: Some("tags"), span: Some(24..30) }"#
+    );
+    assert_compact_debug_snapshot!(
+        super::from_str::<Item>("name=Pen").unwrap_err(),
+        @r#"Error { kind: MissingField, message: "missing field `price`", key: Some("price"), span: None }"#
+    );
+    assert_compact_debug_snapshot!(
+        super::from_str::<Item>("name=Pen&price=1&name=Cap").unwrap_err(),
+        @r#"Error { kind: Duplicate, message: "duplicate key `name`", key: Some("name"), span: Some(17..25) }"#
+    );
+
+    let input = "items[0][name]=Pen&items[0][price]=1&items[1][name]=Cap&items[1][price]=-1";
+    let deserializer =
+        super::Deserializer::from_bytes(input.as_bytes()).key_notation(KeyNotation::Brackets);
+    assert_compact_debug_snapshot!(
+        Order::deserialize(deserializer).unwrap_err(),
+        @r#"Error { kind: Parse, message: "invalid digit found in string", key: Some("items[1][price]"), span: Some(56..74) }"#
+    );
+
+    let deserializer = super::Deserializer::from_bytes(b"name=Pen&price=1&tags=1&tags=2")
+        .limits(Limits::new().max_values_per_key(1));
+    assert_compact_debug_snapshot!(
+        Item::deserialize(deserializer).unwrap_err(),
+        @r#"Error { kind: LimitExceeded, message: "limit exceeded: `tags` has more than 1 values", key: Some("tags"), span: None }"#
+    );
+}
+
+#[test]
+fn deserialize_collect_errors() {
+    #[derive(Deserialize, Debug)]
+    #[allow(dead_code)]
+    struct Signup {
+        name: String,
+        age: u8,
+        #[serde(default)]
+        tags: Vec<u8>,
+        newsletter: Option<bool>,
+    }
+
+    fn messages(errors: &super::FormErrors) -> Vec<String> {
+        errors.iter().map(|e| e.to_string()).collect()
+    }
+
+    assert_compact_debug_snapshot!(
+        super::from_str_collect_errors::<Signup>("name=Jane&age=30&tags=1"),
+        @r#"Ok(Signup { name: "Jane", age: 30, tags: [1], newsletter: None })"#
+    );
+
+    let errors = super::from_str_collect_errors::<Signup>(
+        "name=Jane&name=Joe&age=300&tags=1&tags=x&newsletter=maybe",
+    )
+    .unwrap_err();
+    assert_compact_debug_snapshot!(
+        messages(&errors),
+        @r#"["name: duplicate key `name`", "age: number too large to fit in target type", "tags: invalid digit found in string", "newsletter: provided string was not `true` or `false`"]"#
+    );
+    assert_compact_debug_snapshot!(errors.get("tags").unwrap().values(), @r#"["1", "x"]"#);
+    assert_compact_debug_snapshot!(errors.get("tags").unwrap().error().span(), @"Some(34..40)");
+    assert_compact_debug_snapshot!(errors.values("name").collect::<Vec<_>>(), @r#"["Jane", "Joe"]"#);
+
+    #[derive(Deserialize, Debug)]
+    #[allow(dead_code)]
+    struct Abc {
+        a: u8,
+        b: u8,
+        c: u8,
+    }
+
+    assert_compact_debug_snapshot!(
+        messages(&super::from_str_collect_errors::<Abc>("a=x&c=1").unwrap_err()),
+        @r#"["a: invalid digit found in string", "b: missing field `b`"]"#
+    );
+    assert_compact_debug_snapshot!(
+        messages(&super::from_str_collect_errors::<Abc>("").unwrap_err()),
+        @r#"["a: missing field `a`", "b: missing field `b`", "c: missing field `c`"]"#
+    );
+
+    #[derive(Deserialize, Debug)]
+    #[allow(dead_code)]
+    struct Form {
+        user: Signup,
+        id: u32,
+    }
+
+    let deserializer = super::Deserializer::from_bytes(b"user[name]=Jane&user[age]=old")
+        .key_notation(KeyNotation::Brackets);
+    let errors = deserializer.collect_errors::<Form>().unwrap_err();
+    assert_compact_debug_snapshot!(
+        messages(&errors),
+        @r#"["user[age]: invalid digit found in string", "id: missing field `id`"]"#
+    );
+
+    let deserializer = super::Deserializer::from_bytes(b"name=%zz").strict(true);
+    assert_compact_debug_snapshot!(
+        messages(&deserializer.collect_errors::<Signup>().unwrap_err()),
+        @r#"["invalid percent-escape `%zz` at byte 5"]"#
+    );
+}
+
+#[test]
+fn deserialize_collect_many_errors() {
+    #[derive(Deserialize, Debug)]
+    #[allow(dead_code)]
+    enum Size {
+        Small,
+        Large,
+    }
+
+    #[derive(Deserialize, Debug)]
+    #[allow(dead_code)]
+    struct Item {
+        sku: String,
+        size: Size,
+        price: u32,
+    }
+
+    #[derive(Deserialize, Debug)]
+    #[allow(dead_code)]
+    struct Order {
+        items: Vec<Item>,
+    }
+
+    let input: String = (0..1000)
+        .map(|i| format!("items[{}][sku]=a&items[{}][size]=Small&items[{}][price]=x&", i, i, i))
+        .collect();
+    let input = input.replacen("Small", "Huge", 1);
+    let deserializer = super::Deserializer::from_bytes(input.as_bytes())
+        .key_notation(KeyNotation::Brackets)
+        .limits(Limits::new().max_pairs(3000));
+    let errors = deserializer.collect_errors::<Order>().unwrap_err();
+    assert_eq!(errors.len(), 1001);
+    assert_compact_debug_snapshot!(
+        errors.iter().take(3).map(|e| e.to_string()).collect::<Vec<_>>(),
+        @r#"["items[0][size]: unknown variant `Huge`, expected `Small` or `Large`", "items[0][price]: invalid digit found in string", "items[1][price]: invalid digit found in string"]"#
+    );
+    assert_compact_debug_snapshot!(errors.iter().last().unwrap().error(), @r#"Error { kind: Parse, message: "invalid digit found in string", key: Some("items[999][price]"), span: Some(60649..60668) }"#);
+    assert_compact_debug_snapshot!(errors.get("items[500][price]").unwrap().values(), @r#"["x"]"#);
+}
+
+#[test]
+fn deserialize_strict() {
+    type Map = BTreeMap<String, String>;
+
+    assert_compact_debug_snapshot!(
+        super::from_bytes::<Map>(b"a=%zz&b=%4&=c&d=%FF"),
+        @r#"Ok({"": "c", "a": "%zz", "b": "%4", "d": "�"})"#
+    );
+    assert_compact_debug_snapshot!(
+        from_bytes::<Map>(b"a=%C3%A9+x&&b=", |d| d.strict(true)),
+        @r#"Ok({"a": "é x", "b": ""})"#
+    );
+    assert_compact_debug_snapshot!(
+        from_bytes::<Map>(b"a=1&b=%zz", |d| d.strict(true)),
+        @r#"Err(Error { kind: Syntax, message: "invalid percent-escape `%zz` at byte 6", key: None, span: Some(4..9) })"#
+    );
+    assert_compact_debug_snapshot!(
+        from_bytes::<Map>(b"a=1&b=%4", |d| d.strict(true)),
+        @r#"Err(Error { kind: Syntax, message: "truncated percent-escape at byte 6", key: None, span: Some(4..8) })"#
+    );
+    assert_compact_debug_snapshot!(
+        from_bytes::<Map>(b"a=1&b=%C3%A9%FF", |d| d.strict(true)),
+        @r#"Err(Error { kind: Syntax, message: "invalid UTF-8 at byte 12", key: None, span: Some(4..15) })"#
+    );
+    assert_compact_debug_snapshot!(
+        from_bytes::<Map>(b"a=1&=2", |d| d.strict(true)),
+        @r#"Err(Error { kind: Syntax, message: "empty key at byte 4", key: None, span: Some(4..6) })"#
+    );
+    assert_compact_debug_snapshot!(
+        super::from_bytes::<Vec<(String, String)>>(b"a=1&=2"),
+        @r#"Ok([("a", "1"), ("", "2")])"#
+    );
+
+    let mut parser = super::Parser::new().strict(true);
+    parser.feed(b"a=1&b=").unwrap();
+    parser.feed(b"%C3").unwrap();
+    assert_compact_debug_snapshot!(
+        BTreeMap::<String, String>::deserialize(parser.finish()),
+        @r#"Err(Error { kind: Syntax, message: "invalid UTF-8 at byte 6", key: None, span: Some(4..9) })"#
+    );
+}
+
+#[cfg(feature = "encoding")]
+#[test]
+fn deserialize_with_encoding() {
+    type Map = BTreeMap<String, String>;
+
+    let input = b"name=Ren%E9e&note=%80+%26+x";
+    assert_compact_debug_snapshot!(
+        from_bytes::<Map>(input, |d| d.encoding(encoding_rs::WINDOWS_1252)),
+        @r#"Ok({"name": "Renée", "note": "€ & x"})"#
+    );
+    assert_compact_debug_snapshot!(
+        from_bytes::<Map>(input, |d| d),
+        @r#"Ok({"name": "Ren�e", "note": "� & x"})"#
+    );
+
+    let input = b"_charset_=shift_jis&city=%93%8C%8B%9E";
+    assert_compact_debug_snapshot!(
+        from_bytes::<Map>(input, |d| d.detect_charset(true)),
+        @r#"Ok({"_charset_": "shift_jis", "city": "東京"})"#
+    );
+    assert_compact_debug_snapshot!(
+        from_bytes::<Map>(b"_charset_=bogus&name=Ren%E9e", |d| d
+            .encoding(encoding_rs::WINDOWS_1252)
+            .detect_charset(true)),
+        @r#"Ok({"_charset_": "bogus", "name": "Renée"})"#
+    );
+    assert_compact_debug_snapshot!(
+        from_bytes::<Map>(b"_charset_=UTF-16&name=Ren%C3%A9e", |d| d.detect_charset(true)),
+        @r#"Ok({"_charset_": "UTF-16", "name": "Renée"})"#
+    );
+
+    let input = b"name=Ren%E9e&note=%zz";
+    assert_compact_debug_snapshot!(
+        from_bytes::<Map>(input, |d| d.encoding(encoding_rs::WINDOWS_1252).strict(true)),
+        @r#"Err(Error { kind: Syntax, message: "invalid percent-escape `%zz` at byte 18", key: None, span: Some(13..21) })"#
+    );
+}
+
+#[test]
+fn deserialize_from_pairs() {
+    #[allow(dead_code)]
+    #[derive(Debug, Deserialize)]
+    struct Form<'a> {
+        #[serde(borrow)]
+        name: Cow<'a, str>,
+        user: BTreeMap<String, u8>,
+        tag: Vec<String>,
+    }
+
+    let pairs = [("name", "a+b%20"), ("user[age]", "30"), ("tag", "x"), ("tag", "y")];
+    let deserializer = super::Deserializer::from_pairs(pairs).key_notation(KeyNotation::Brackets);
+    assert_compact_debug_snapshot!(
+        Form::deserialize(deserializer),
+        @r#"Ok(Form { name: "a+b%20", user: {"age": 30}, tag: ["x", "y"] })"#
+    );
+
+    let pairs = [("name", "x"), ("name", "y")].map(|(k, v)| (String::from(k), String::from(v)));
+    assert_compact_debug_snapshot!(
+        super::from_pairs::<Form<'_>, _, _, _>(pairs),
+        @r#"Err(Error { kind: Duplicate, message: "duplicate key `name`", key: Some("name"), span: None })"#
+    );
+}
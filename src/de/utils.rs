@@ -1,5 +1,10 @@
 use core::{any::TypeId, marker::PhantomData, mem};
 
+use serde_core::{
+    de::{self, value::Error, Deserialize, Error as _},
+    forward_to_deserialize_any,
+};
+
 /// Produces type IDs that are compatible with `TypeId::of::<T>`, but without
 /// `T: 'static` bound.
 ///
@@ -8,9 +13,9 @@ use core::{any::TypeId, marker::PhantomData, mem};
 /// and `Struct<'b>`, regardless of the relationship of `'a` and `'b`.
 ///
 /// It is however a safe function since `TypeId` itself doesn't allow one to do
-/// anything dangerous in safe code. In this crate, it's only used to specialize
-/// behavior based on the type, there is no transmuting going on outside of this
-/// function.
+/// anything dangerous in safe code. In this crate, it's used to specialize
+/// behavior based on the type, and to pass on errors of this crate unchanged
+/// in `Error::into_error`, the only other place where transmuting is done.
 pub(crate) fn non_static_type_id<T: ?Sized>() -> TypeId {
     // Copied from the castaway crate, Copyright (c) 2021 Stephen M. Coakley
 
@@ -34,3 +39,45 @@ pub(crate) fn non_static_type_id<T: ?Sized>() -> TypeId {
         mem::transmute::<&dyn NonStaticAny, &(dyn NonStaticAny + 'static)>(&phantom_data)
     })
 }
+
+/// Returns the field names of `T`, if it is deserialized as a struct.
+///
+/// This works by deserializing `T` from a deserializer that records the field
+/// names passed to `deserialize_struct` and always fails afterwards.
+pub(crate) fn struct_fields<'de, T: Deserialize<'de>>() -> Option<&'static [&'static str]> {
+    struct FieldsProbe<'a>(&'a mut Option<&'static [&'static str]>);
+
+    impl<'de> de::Deserializer<'de> for FieldsProbe<'_> {
+        type Error = Error;
+
+        fn deserialize_any<V>(self, _visitor: V) -> Result<V::Value, Self::Error>
+        where
+            V: de::Visitor<'de>,
+        {
+            Err(Error::custom("not a struct"))
+        }
+
+        fn deserialize_struct<V>(
+            self,
+            _name: &'static str,
+            fields: &'static [&'static str],
+            _visitor: V,
+        ) -> Result<V::Value, Self::Error>
+        where
+            V: de::Visitor<'de>,
+        {
+            *self.0 = Some(fields);
+            Err(Error::custom("not a struct"))
+        }
+
+        forward_to_deserialize_any! {
+            bool u8 u16 u32 u64 u128 i8 i16 i32 i64 i128 f32 f64 char str string
+            bytes byte_buf option unit unit_struct newtype_struct seq tuple
+            tuple_struct map enum identifier ignored_any
+        }
+    }
+
+    let mut fields = None;
+    let _ = T::deserialize(FieldsProbe(&mut fields));
+    fields
+}
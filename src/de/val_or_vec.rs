@@ -1,11 +1,12 @@
-use alloc::vec::{self, Vec};
+use alloc::{
+    borrow::Cow,
+    vec::{self, Vec},
+};
 use core::{hint::unreachable_unchecked, iter, mem};
 
-use serde_core::de::{
-    self,
-    value::{Error, SeqDeserializer},
-    Deserializer, IntoDeserializer,
-};
+use serde_core::de::{self, Deserializer, IntoDeserializer};
+
+use super::{part::Part, DuplicateKeys, Error, ErrorKind};
 
 #[derive(Debug)]
 pub(crate) enum ValOrVec<T> {
@@ -39,14 +40,10 @@ impl<T> ValOrVec<T> {
         }
     }
 
-    fn deserialize_val<U, E, F>(self, f: F) -> Result<U, E>
-    where
-        F: FnOnce(T) -> Result<U, E>,
-        E: de::Error,
-    {
+    pub fn len(&self) -> usize {
         match self {
-            ValOrVec::Val(val) => f(val),
-            ValOrVec::Vec(_) => Err(de::Error::custom("unsupported value")),
+            Self::Val(_) => 1,
+            Self::Vec(vec) => vec.len(),
         }
     }
 }
@@ -85,10 +82,131 @@ impl<T> Iterator for IntoIter<T> {
     }
 }
 
-impl<'de, T> IntoDeserializer<'de> for ValOrVec<T>
+/// The values of a key, ready to be deserialized.
+pub(crate) struct Values<'de> {
+    values: ValOrVec<Part<'de>>,
+    /// The key, only set for nested keys or once there is more than one
+    /// value. It is added to errors about the values.
+    key: Option<Cow<'de, str>>,
+    duplicate_keys: DuplicateKeys,
+    infer_scalars: bool,
+}
+
+impl<'de> Values<'de> {
+    pub fn new(value: Part<'de>, duplicate_keys: DuplicateKeys) -> Self {
+        Values { values: ValOrVec::Val(value), key: None, duplicate_keys, infer_scalars: false }
+    }
+
+    /// Sets whether `deserialize_any` infers booleans and numbers, see
+    /// `Deserializer::infer_scalars`.
+    pub fn infer_scalars(mut self, infer_scalars: bool) -> Self {
+        self.infer_scalars = infer_scalars;
+        self
+    }
+
+    /// Returns the values of a flat key after grouping them.
+    pub fn grouped(
+        key: &Cow<'de, str>,
+        values: ValOrVec<Part<'de>>,
+        duplicate_keys: DuplicateKeys,
+    ) -> Self {
+        // The key is only needed for errors about duplicate values.
+        let key = (values.len() > 1).then(|| key.clone());
+        Values { values, key, duplicate_keys, infer_scalars: false }
+    }
+
+    /// Sets the full key, for values of nested keys.
+    pub fn with_key(mut self, key: Cow<'de, str>) -> Self {
+        self.key = Some(key);
+        self
+    }
+
+    pub fn push(&mut self, value: Part<'de>) {
+        self.values.push(value);
+    }
+
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    fn deserialize_val<U, F>(self, f: F) -> Result<U, Error>
+    where
+        F: FnOnce(Part<'de>) -> Result<U, Error>,
+    {
+        let key = self.key;
+        let result = match self.values {
+            ValOrVec::Val(val) => deserialize_spanned(val, f),
+            ValOrVec::Vec(vec) => {
+                let second_span = vec[1].span();
+                let val = match self.duplicate_keys {
+                    DuplicateKeys::Error => None,
+                    DuplicateKeys::First => vec.into_iter().next(),
+                    DuplicateKeys::Last => vec.into_iter().next_back(),
+                };
+
+                match val {
+                    Some(val) => deserialize_spanned(val, f),
+                    None => Err(Error::new(
+                        ErrorKind::Duplicate,
+                        format_args!("duplicate key `{}`", key.as_deref().unwrap_or_default()),
+                    )
+                    .with_span(second_span)),
+                }
+            }
+        };
+
+        with_key(result, key.as_deref())
+    }
+}
+
+fn deserialize_spanned<'de, U, F>(val: Part<'de>, f: F) -> Result<U, Error>
 where
-    T: IntoDeserializer<'de> + Deserializer<'de, Error = Error>,
+    F: FnOnce(Part<'de>) -> Result<U, Error>,
 {
+    let span = val.span();
+    f(val).map_err(|e| e.with_span(span))
+}
+
+fn with_key<U>(result: Result<U, Error>, key: Option<&str>) -> Result<U, Error> {
+    match key {
+        Some(key) => result.map_err(|e| e.with_key(key)),
+        None => result,
+    }
+}
+
+/// A `SeqAccess` over values that adds the span of a value to errors about
+/// it.
+struct ValuesAccess<'de> {
+    values: IntoIter<Part<'de>>,
+    infer_scalars: bool,
+}
+
+impl<'de> de::SeqAccess<'de> for ValuesAccess<'de> {
+    type Error = Error;
+
+    fn next_element_seed<T>(&mut self, seed: T) -> Result<Option<T::Value>, Self::Error>
+    where
+        T: de::DeserializeSeed<'de>,
+    {
+        match self.values.next() {
+            Some(val) if self.infer_scalars => {
+                let values = Values::new(val, DuplicateKeys::Error).infer_scalars(true);
+                seed.deserialize(values).map(Some)
+            }
+            Some(val) => deserialize_spanned(val, |val| seed.deserialize(val)).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        match &self.values {
+            IntoIter::Val(iter) => Some(iter.len()),
+            IntoIter::Vec(iter) => Some(iter.len()),
+        }
+    }
+}
+
+impl<'de> IntoDeserializer<'de, Error> for Values<'de> {
     type Deserializer = Self;
 
     fn into_deserializer(self) -> Self::Deserializer {
@@ -108,19 +226,19 @@ macro_rules! forward_to_part {
     }
 }
 
-impl<'de, T> Deserializer<'de> for ValOrVec<T>
-where
-    T: IntoDeserializer<'de> + Deserializer<'de, Error = Error>,
-{
+impl<'de> Deserializer<'de> for Values<'de> {
     type Error = Error;
 
     fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, Self::Error>
     where
         V: de::Visitor<'de>,
     {
-        match self {
-            Self::Val(val) => val.deserialize_any(visitor),
-            Self::Vec(_) => self.deserialize_seq(visitor),
+        match self.values {
+            ValOrVec::Val(_) if self.infer_scalars => {
+                self.deserialize_val(move |val| val.deserialize_inferred(visitor))
+            }
+            ValOrVec::Val(_) => self.deserialize_val(move |val| val.deserialize_any(visitor)),
+            ValOrVec::Vec(_) => self.deserialize_seq(visitor),
         }
     }
 
@@ -128,7 +246,9 @@ where
     where
         V: de::Visitor<'de>,
     {
-        visitor.visit_seq(SeqDeserializer::new(self.into_iter()))
+        let values = self.values.into_iter();
+        let result = visitor.visit_seq(ValuesAccess { values, infer_scalars: self.infer_scalars });
+        with_key(result, self.key.as_deref())
     }
 
     fn deserialize_enum<V>(
@@ -207,8 +327,8 @@ where
     where
         V: de::Visitor<'de>,
     {
-        match self {
-            ValOrVec::Val(val) => val.deserialize_option(visitor),
+        match self.values {
+            ValOrVec::Val(_) => self.deserialize_val(move |val| val.deserialize_option(visitor)),
             ValOrVec::Vec(_) => visitor.visit_some(self),
         }
     }
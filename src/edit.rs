@@ -0,0 +1,185 @@
+//! Editing query strings without changing the pairs that are left alone.
+
+use alloc::{borrow::Cow, string::String, vec::Vec};
+use core::fmt;
+
+/// A parsed query string that can be edited and written back.
+///
+/// Deserializing and serializing again normalizes the whole input: `%20`
+/// becomes `+`, unnecessary escapes are removed and so on. A `Document` keeps
+/// the original text of every pair instead, and only writes the pairs that
+/// were set or appended in the encoding of [`to_string`](crate::to_string).
+/// Keys are matched after percent-decoding.
+///
+/// ```
+/// use serde_html_form::edit::Document;
+///
+/// let mut query = Document::parse("q=caf%C3%A9%20au%20lait&page=2&utm_source=mail&sort=new");
+/// query.set("page", "3");
+/// query.retain(|key| !key.starts_with("utm_"));
+/// query.append("view", "grid list");
+///
+/// assert_eq!(query.get("q").as_deref(), Some("café au lait"));
+/// assert_eq!(query.to_string(), "q=caf%C3%A9%20au%20lait&page=3&sort=new&view=grid+list");
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Document<'a> {
+    pairs: Vec<Pair<'a>>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Pair<'a> {
+    /// A pair from the input, with its decoded key. Empty segments, like the
+    /// one in `a=1&&b=2`, have no key.
+    Original { raw: &'a str, key: Option<Cow<'a, str>> },
+    /// A pair that was set or appended.
+    New { key: String, value: String },
+}
+
+impl<'a> Pair<'a> {
+    fn key(&self) -> Option<&str> {
+        match self {
+            Pair::Original { key, .. } => key.as_deref(),
+            Pair::New { key, .. } => Some(key),
+        }
+    }
+
+    fn value(&self) -> Cow<'_, str> {
+        match self {
+            Pair::Original { raw, .. } => match form_urlencoded::parse(raw.as_bytes()).next() {
+                Some((_, value)) => value,
+                None => Cow::Borrowed(""),
+            },
+            Pair::New { value, .. } => Cow::Borrowed(value),
+        }
+    }
+}
+
+impl<'a> Document<'a> {
+    /// Parses a query string or `application/x-www-form-urlencoded` body.
+    ///
+    /// The input should not include the leading `?` of a query string.
+    pub fn parse(input: &'a str) -> Self {
+        let pairs = if input.is_empty() {
+            Vec::new()
+        } else {
+            input
+                .split('&')
+                .map(|raw| {
+                    let key = form_urlencoded::parse(raw.as_bytes()).next().map(|(key, _)| key);
+                    Pair::Original { raw, key }
+                })
+                .collect()
+        };
+
+        Document { pairs }
+    }
+
+    /// Returns `true` if there is at least one pair with the given key.
+    pub fn contains_key(&self, key: &str) -> bool {
+        self.pairs.iter().any(|pair| pair.key() == Some(key))
+    }
+
+    /// Returns the first decoded value for the given key.
+    pub fn get(&self, key: &str) -> Option<Cow<'_, str>> {
+        self.pairs.iter().find(|pair| pair.key() == Some(key)).map(Pair::value)
+    }
+
+    /// Returns all decoded values for the given key, in order.
+    pub fn get_all<'b>(&'b self, key: &'b str) -> impl Iterator<Item = Cow<'b, str>> + 'b {
+        self.pairs.iter().filter(move |pair| pair.key() == Some(key)).map(Pair::value)
+    }
+
+    /// Sets the value of the given key.
+    ///
+    /// The first pair with the key is replaced in place and the others are
+    /// removed. If there is none, the pair is appended.
+    pub fn set(&mut self, key: &str, value: &str) {
+        let mut new = Some(Pair::New { key: key.into(), value: value.into() });
+        self.pairs.retain_mut(|pair| {
+            if pair.key() != Some(key) {
+                return true;
+            }
+            match new.take() {
+                Some(new) => {
+                    *pair = new;
+                    true
+                }
+                None => false,
+            }
+        });
+
+        if let Some(new) = new {
+            self.pairs.push(new);
+        }
+    }
+
+    /// Appends a pair, keeping any existing values for the key.
+    pub fn append(&mut self, key: &str, value: &str) {
+        self.pairs.push(Pair::New { key: key.into(), value: value.into() });
+    }
+
+    /// Removes all pairs with the given key.
+    pub fn remove(&mut self, key: &str) {
+        self.retain(|k| k != key);
+    }
+
+    /// Keeps only the pairs whose decoded key matches the predicate.
+    pub fn retain(&mut self, mut f: impl FnMut(&str) -> bool) {
+        self.pairs.retain(|pair| pair.key().map_or(true, &mut f));
+    }
+}
+
+impl fmt::Display for Document<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, pair) in self.pairs.iter().enumerate() {
+            if i > 0 {
+                f.write_str("&")?;
+            }
+            match pair {
+                Pair::Original { raw, .. } => f.write_str(raw)?,
+                Pair::New { key, value } => {
+                    form_urlencoded::byte_serialize(key.as_bytes())
+                        .try_for_each(|s| f.write_str(s))?;
+                    f.write_str("=")?;
+                    form_urlencoded::byte_serialize(value.as_bytes())
+                        .try_for_each(|s| f.write_str(s))?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::{string::ToString as _, vec::Vec};
+
+    use super::Document;
+
+    #[test]
+    fn untouched() {
+        for input in ["", "a", "a=1&&b=%2x&c=%7e+%20", "&=&a==1&"] {
+            assert_eq!(Document::parse(input).to_string(), input);
+        }
+    }
+
+    #[test]
+    fn decoded_keys() {
+        let mut doc = Document::parse("a%5Bb%5D=1&a[b]=2&a+b=3&x=%41");
+        assert_eq!(doc.get_all("a[b]").collect::<Vec<_>>(), ["1", "2"]);
+        assert_eq!(doc.get("x").as_deref(), Some("A"));
+
+        doc.set("a[b]", "é");
+        doc.remove("a b");
+        assert_eq!(doc.to_string(), "a%5Bb%5D=%C3%A9&x=%41");
+        assert!(!doc.contains_key("a b"));
+    }
+
+    #[test]
+    fn empty_segments() {
+        let mut doc = Document::parse("&a=1&&");
+        doc.set("b", "2");
+        assert_eq!(doc.to_string(), "&a=1&&&b=2");
+    }
+}
@@ -0,0 +1,228 @@
+//! A dynamic representation of a form.
+
+use alloc::{string::String, vec, vec::Vec};
+use core::{fmt, iter::FromIterator, mem};
+
+use serde_core::{de, ser};
+
+use crate::ser::{Error, PairSink};
+
+/// The pairs of a form, in order and including duplicate keys.
+///
+/// Unlike a map, a `Form` keeps the exact order of its pairs, so it can be
+/// inspected or rewritten and serialized again without changing anything
+/// else. It implements `Serialize` and `Deserialize` as a sequence of pairs.
+///
+/// ```
+/// use serde::Deserialize;
+/// use serde_html_form::Form;
+///
+/// #[derive(Debug, PartialEq, Deserialize)]
+/// struct Order {
+///     item: Vec<String>,
+///     quantity: u32,
+/// }
+///
+/// let mut form: Form = serde_html_form::from_str("item=pen&quantity=2&item=ink").unwrap();
+/// assert_eq!(form.get("item"), Some("pen"));
+/// assert_eq!(form.get_all("item").collect::<Vec<_>>(), ["pen", "ink"]);
+///
+/// form.insert("quantity", "3");
+/// form.append("item", "paper");
+/// assert_eq!(
+///     serde_html_form::to_string(&form).unwrap(),
+///     "item=pen&quantity=3&item=ink&item=paper",
+/// );
+///
+/// let order: Order =
+///     serde_html_form::from_str(&serde_html_form::to_string(&form).unwrap()).unwrap();
+/// assert_eq!(order.quantity, 3);
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Form {
+    pairs: Vec<(String, String)>,
+}
+
+impl Form {
+    /// Returns an empty `Form`.
+    pub fn new() -> Self {
+        Form::default()
+    }
+
+    /// Returns the number of pairs.
+    pub fn len(&self) -> usize {
+        self.pairs.len()
+    }
+
+    /// Returns `true` if there are no pairs.
+    pub fn is_empty(&self) -> bool {
+        self.pairs.is_empty()
+    }
+
+    /// Returns `true` if there is at least one pair with the given key.
+    pub fn contains_key(&self, key: &str) -> bool {
+        self.pairs.iter().any(|(k, _)| k == key)
+    }
+
+    /// Returns the first value for the given key.
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.pairs.iter().find(|(k, _)| k == key).map(|(_, v)| v.as_str())
+    }
+
+    /// Returns all values for the given key, in order.
+    pub fn get_all<'a>(&'a self, key: &'a str) -> impl Iterator<Item = &'a str> + 'a {
+        self.pairs.iter().filter(move |(k, _)| k == key).map(|(_, v)| v.as_str())
+    }
+
+    /// Sets the value of the given key, returning the previous first value.
+    ///
+    /// The first pair with the key keeps its position and the others are
+    /// removed. If there is none, the pair is appended.
+    pub fn insert(&mut self, key: impl Into<String>, value: impl Into<String>) -> Option<String> {
+        let key = key.into();
+        let mut value = Some(value.into());
+        let mut previous = None;
+        self.pairs.retain_mut(|(k, v)| {
+            if *k != key {
+                return true;
+            }
+            match value.take() {
+                Some(value) => {
+                    previous = Some(mem::replace(v, value));
+                    true
+                }
+                None => false,
+            }
+        });
+
+        if let Some(value) = value {
+            self.pairs.push((key, value));
+        }
+        previous
+    }
+
+    /// Appends a pair, keeping any existing values for the key.
+    pub fn append(&mut self, key: impl Into<String>, value: impl Into<String>) {
+        self.pairs.push((key.into(), value.into()));
+    }
+
+    /// Removes all pairs with the given key, returning their values in order.
+    pub fn remove(&mut self, key: &str) -> Vec<String> {
+        let mut removed = Vec::new();
+        self.pairs.retain_mut(|(k, v)| {
+            if k != key {
+                return true;
+            }
+            removed.push(mem::take(v));
+            false
+        });
+        removed
+    }
+
+    /// Returns an iterator over the pairs, in order.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> + '_ {
+        self.pairs.iter().map(|(k, v)| (k.as_str(), v.as_str()))
+    }
+}
+
+impl<K: Into<String>, V: Into<String>> FromIterator<(K, V)> for Form {
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        let mut form = Form::new();
+        form.extend(iter);
+        form
+    }
+}
+
+impl<K: Into<String>, V: Into<String>> Extend<(K, V)> for Form {
+    fn extend<I: IntoIterator<Item = (K, V)>>(&mut self, iter: I) {
+        self.pairs.extend(iter.into_iter().map(|(k, v)| (k.into(), v.into())));
+    }
+}
+
+impl IntoIterator for Form {
+    type Item = (String, String);
+    type IntoIter = vec::IntoIter<(String, String)>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.pairs.into_iter()
+    }
+}
+
+/// Values that are not valid UTF-8 result in an error.
+impl PairSink for Form {
+    fn append_pair(&mut self, key: &str, value: &str) -> Result<(), Error> {
+        self.append(key, value);
+        Ok(())
+    }
+}
+
+impl ser::Serialize for Form {
+    fn serialize<S: ser::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.iter())
+    }
+}
+
+impl<'de> de::Deserialize<'de> for Form {
+    fn deserialize<D: de::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_seq(FormVisitor)
+    }
+}
+
+struct FormVisitor;
+
+impl<'de> de::Visitor<'de> for FormVisitor {
+    type Value = Form;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        formatter.write_str("a sequence of key-value pairs")
+    }
+
+    fn visit_seq<A: de::SeqAccess<'de>>(self, mut seq: A) -> Result<Form, A::Error> {
+        let mut pairs = Vec::with_capacity(seq.size_hint().unwrap_or(0).min(4096));
+        while let Some(pair) = seq.next_element()? {
+            pairs.push(pair);
+        }
+        Ok(Form { pairs })
+    }
+
+    fn visit_map<A: de::MapAccess<'de>>(self, mut map: A) -> Result<Form, A::Error> {
+        let mut pairs = Vec::with_capacity(map.size_hint().unwrap_or(0).min(4096));
+        while let Some(pair) = map.next_entry()? {
+            pairs.push(pair);
+        }
+        Ok(Form { pairs })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::{vec, vec::Vec};
+
+    use super::Form;
+
+    #[test]
+    fn insert() {
+        let mut form: Form = [("a", "1"), ("b", "2"), ("a", "3")].into_iter().collect();
+        assert_eq!(form.insert("a", "4"), Some("1".into()));
+        assert_eq!(form.iter().collect::<Vec<_>>(), [("a", "4"), ("b", "2")]);
+        assert_eq!(form.insert("c", "5"), None);
+        assert_eq!(form.iter().collect::<Vec<_>>(), [("a", "4"), ("b", "2"), ("c", "5")]);
+    }
+
+    #[test]
+    fn remove() {
+        let mut form: Form = [("a", "1"), ("b", "2"), ("a", "3")].into_iter().collect();
+        assert_eq!(form.remove("a"), vec!["1", "3"]);
+        assert_eq!(form.remove("a"), Vec::<&str>::new());
+        assert_eq!(form.iter().collect::<Vec<_>>(), [("b", "2")]);
+    }
+
+    #[test]
+    fn round_trip() {
+        let input = "b=2&a=1&b=&c=%F0%9F%98%80+x&a=3";
+        let form: Form = crate::from_str(input).unwrap();
+        assert_eq!(form.len(), 5);
+        assert_eq!(form.get("c"), Some("😀 x"));
+        assert_eq!(crate::to_string(&form).unwrap(), input);
+    }
+}
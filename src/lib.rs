@@ -1,4 +1,5 @@
-#![doc = include_str!("../README.md")]
+// The examples in the README need the `alloc` feature.
+#![cfg_attr(any(feature = "alloc", not(doctest)), doc = include_str!("../README.md"))]
 #![no_std]
 #![warn(
     missing_docs,
@@ -20,15 +21,58 @@
     clippy::wildcard_imports
 )]
 
+#[cfg(feature = "alloc")]
 extern crate alloc;
 #[cfg(feature = "std")]
 extern crate std;
 
+#[cfg(feature = "alloc")]
 pub mod de;
+#[cfg(feature = "alloc")]
+pub mod edit;
+#[cfg(feature = "alloc")]
+mod form;
 pub mod ser;
 
+/// How keys of nested values are written in a form.
+///
+/// By default, keys are flat: `user[name]` is just a key that happens to
+/// contain brackets, and can only be matched with
+/// `#[serde(rename = "user[name]")]`. With another notation, such keys are
+/// interpreted as paths into nested structs and maps.
+///
+/// When deserializing a sequence from indexed keys like `items[1]` and
+/// `items[0]`, elements are ordered by their index. Gaps are skipped, so
+/// `items[0]=a&items[5]=b` results in `["a", "b"]`. Indices larger than 1000
+/// are rejected, see [`Limits::max_index`](de::Limits::max_index).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum KeyNotation {
+    /// Keys are not interpreted; nested structs and maps are not supported.
+    #[default]
+    Flat,
+
+    /// Rails / PHP style bracket notation, like `user[address][city]`.
+    ///
+    /// A trailing `[]`, like in `tags[]`, appends to a sequence and is
+    /// equivalent to just `tags`. Sequences of structs are written with
+    /// indices, like `items[0][name]`.
+    Brackets,
+
+    /// Dotted paths, like `user.address.city`.
+    ///
+    /// This is the notation used by Spring and ASP.NET model binding, Go's
+    /// `gorilla/schema` and HTML image buttons (`submit.x=10&submit.y=20`).
+    /// Bracketed segments are accepted as well, and sequences of structs are
+    /// written with indices like `items[0].name`. When deserializing,
+    /// `items.0.name` is also accepted.
+    Dots,
+}
+
+#[cfg(feature = "alloc")]
 #[doc(inline)]
 pub use crate::{
     de::{from_bytes, from_str, Deserializer},
+    form::Form,
     ser::{push_to_string, to_string, Serializer},
 };
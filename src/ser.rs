@@ -2,16 +2,27 @@
 
 mod error;
 mod key;
+#[cfg(feature = "alloc")]
+mod nested;
 mod pair;
+pub mod pairs;
 mod part;
+mod sink;
 mod value;
 
-use alloc::{borrow::Cow, str, string::String};
+#[cfg(feature = "alloc")]
+use alloc::{string::String, vec::Vec};
+use core::fmt;
 
-use form_urlencoded::{Serializer as UrlEncodedSerializer, Target as UrlEncodedTarget};
+#[cfg(feature = "alloc")]
+use form_urlencoded::Serializer as UrlEncodedSerializer;
 use serde_core::ser;
 
-pub use self::error::Error;
+use self::sink::Encoder;
+pub use self::{
+    error::{Error, ErrorKind},
+    sink::PairSink,
+};
 
 /// Serializes a value into a `application/x-www-form-urlencoded` `String` buffer.
 ///
@@ -23,6 +34,7 @@ pub use self::error::Error;
 ///     Ok("bread=baguette&cheese=comt%C3%A9&meat=ham&fat=butter".to_owned())
 /// );
 /// ```
+#[cfg(feature = "alloc")]
 pub fn to_string<T: ser::Serialize>(input: T) -> Result<String, Error> {
     let mut target = String::new();
     push_to_string(&mut target, input)?;
@@ -40,419 +52,290 @@ pub fn to_string<T: ser::Serialize>(input: T) -> Result<String, Error> {
 ///
 /// assert_eq!(target, "/cook?bread=baguette&cheese=comt%C3%A9&meat=ham&fat=butter");
 /// ```
+#[cfg(feature = "alloc")]
 pub fn push_to_string<T: ser::Serialize>(target: &mut String, input: T) -> Result<(), Error> {
-    let start_position = target.len();
-    let mut urlencoder = UrlEncodedSerializer::for_suffix(target, start_position);
-    input.serialize(Serializer::new(&mut urlencoder))?;
-    urlencoder.finish();
-    Ok(())
+    to_fmt_writer(target, input)
 }
 
-/// A serializer for the `application/x-www-form-urlencoded` format.
-///
-/// * Supported top-level inputs are structs, maps and sequences of pairs,
-///   with or without a given length.
+/// Serializes a value into a `application/x-www-form-urlencoded` byte vector.
 ///
-/// * Supported keys and values are integers, bytes (if convertible to strings),
-///   unit structs and unit variants.
+/// ```
+/// let meal = &[("bread", "baguette"), ("cheese", "comté")];
 ///
-/// * Newtype structs defer to their inner values.
-pub struct Serializer<'input, 'output, Target: UrlEncodedTarget> {
-    urlencoder: &'output mut UrlEncodedSerializer<'input, Target>,
+/// assert_eq!(serde_html_form::ser::to_vec(meal).unwrap(), b"bread=baguette&cheese=comt%C3%A9");
+/// ```
+#[cfg(feature = "alloc")]
+pub fn to_vec<T: ser::Serialize>(input: T) -> Result<Vec<u8>, Error> {
+    to_string(input).map(String::into_bytes)
 }
 
-impl<'input, 'output, Target: 'output + UrlEncodedTarget> Serializer<'input, 'output, Target> {
-    /// Returns a new `Serializer`.
-    pub fn new(urlencoder: &'output mut UrlEncodedSerializer<'input, Target>) -> Self {
-        Serializer { urlencoder }
+/// Serializes a value as `application/x-www-form-urlencoded` into an
+/// [`io::Write`][std::io::Write].
+///
+/// The output is written in many small pieces, so `writer` should be
+/// buffered. If it fails, the error is an [`ErrorKind::Write`] whose source
+/// is the I/O error. Pairs that were written before an error are not
+/// removed.
+///
+/// ```
+/// let meal = &[("bread", "baguette"), ("cheese", "comté")];
+///
+/// let mut body = Vec::new();
+/// serde_html_form::ser::to_writer(&mut body, meal).unwrap();
+/// assert_eq!(body, b"bread=baguette&cheese=comt%C3%A9");
+/// ```
+#[cfg(feature = "std")]
+pub fn to_writer<W: std::io::Write, T: ser::Serialize>(writer: W, input: T) -> Result<(), Error> {
+    let mut writer = sink::IoWriter { inner: writer, error: None };
+    let result = to_fmt_writer(&mut writer, input);
+    match writer.error {
+        Some(error) => Err(Error::io(error)),
+        None => result,
     }
 }
 
-/// Sequence serializer.
-pub struct SeqSerializer<'input, 'output, Target: UrlEncodedTarget> {
-    urlencoder: &'output mut UrlEncodedSerializer<'input, Target>,
-}
-
-/// Tuple serializer.
+/// Serializes a value as `application/x-www-form-urlencoded` into a
+/// [`fmt::Write`], like a `String` or a `fmt::Formatter`.
 ///
-/// Mostly used for arrays.
-pub struct TupleSerializer<'input, 'output, Target: UrlEncodedTarget> {
-    urlencoder: &'output mut UrlEncodedSerializer<'input, Target>,
-}
-
-/// Tuple struct serializer.
+/// Pairs are separated by `&`, but no `&` is written before the first pair,
+/// so the output is appended to what `writer` already contains.
 ///
-/// Never instantiated, tuple structs are not supported.
-pub struct TupleStructSerializer<'input, 'output, T: UrlEncodedTarget> {
-    inner: ser::Impossible<&'output mut UrlEncodedSerializer<'input, T>, Error>,
+/// ```
+/// let mut target = "/cook?".to_owned();
+/// serde_html_form::ser::to_fmt_writer(&mut target, &[("bread", "baguette")]).unwrap();
+/// assert_eq!(target, "/cook?bread=baguette");
+/// ```
+pub fn to_fmt_writer<W, T>(writer: &mut W, input: T) -> Result<(), Error>
+where
+    W: ?Sized + fmt::Write,
+    T: ser::Serialize,
+{
+    let mut encoder = Encoder::new(writer);
+    input.serialize(pairs::Serializer::new(&mut encoder))?;
+    Ok(())
 }
 
-/// Tuple variant serializer.
+/// Returns a value that formats `input` as `application/x-www-form-urlencoded`
+/// with its `Display` implementation.
 ///
-/// Never instantiated, tuple variants are not supported.
-pub struct TupleVariantSerializer<'input, 'output, T: UrlEncodedTarget> {
-    inner: ser::Impossible<&'output mut UrlEncodedSerializer<'input, T>, Error>,
-}
-
-/// Map serializer.
-pub struct MapSerializer<'input, 'output, Target: UrlEncodedTarget> {
-    urlencoder: &'output mut UrlEncodedSerializer<'input, Target>,
-    key: Option<Cow<'static, str>>,
+/// This does not allocate, so it is useful with `format!` and `write!`.
+/// Errors are reported as `fmt::Error`, which makes `format!` and
+/// `to_string` panic, so only use this for values that can always be
+/// serialized.
+///
+/// ```
+/// let meal = &[("bread", "baguette"), ("cheese", "comté")];
+///
+/// assert_eq!(
+///     format!("/cook?{}", serde_html_form::ser::display(meal)),
+///     "/cook?bread=baguette&cheese=comt%C3%A9"
+/// );
+/// ```
+pub fn display<T: ?Sized + ser::Serialize>(input: &T) -> Display<'_, T> {
+    Display { input }
 }
 
-/// Struct serializer.
-pub struct StructSerializer<'input, 'output, Target: UrlEncodedTarget> {
-    urlencoder: &'output mut UrlEncodedSerializer<'input, Target>,
+/// Serializes a value as `application/x-www-form-urlencoded` into a byte
+/// slice, returning the number of bytes written.
+///
+/// This needs neither an allocator nor `std`. If `buf` is too small, the error
+/// is an [`ErrorKind::BufferFull`], and the contents of `buf` are
+/// unspecified.
+///
+/// Without the `alloc` feature, nested key notations are not supported and
+/// the messages of custom errors are not kept. Values that are formatted with
+/// `Display`, like with `Serializer::collect_str`, are limited to 400 bytes,
+/// and keys that are serialized separately from their values, like the keys
+/// of tuples, to 128 bytes. Longer ones result in an [`ErrorKind::TooLong`]
+/// error.
+///
+/// ```
+/// let meal = &[("bread", "baguette"), ("cheese", "comté")];
+///
+/// let mut buf = [0; 64];
+/// let len = serde_html_form::ser::to_slice(&mut buf, meal).unwrap();
+/// assert_eq!(&buf[..len], b"bread=baguette&cheese=comt%C3%A9");
+///
+/// let error = serde_html_form::ser::to_slice(&mut buf[..8], meal).unwrap_err();
+/// assert_eq!(error.kind(), serde_html_form::ser::ErrorKind::BufferFull);
+/// ```
+///
+/// Without the `alloc` feature:
+#[cfg_attr(not(feature = "alloc"), doc = "```")]
+#[cfg_attr(feature = "alloc", doc = "```ignore")]
+/// let mut buf = [0; 1024];
+/// let pairs = [("pi", format_args!("{:.500}", core::f64::consts::PI))];
+/// let error = serde_html_form::ser::to_slice(&mut buf, pairs).unwrap_err();
+/// assert_eq!(error.kind(), serde_html_form::ser::ErrorKind::TooLong);
+/// ```
+pub fn to_slice<T: ser::Serialize>(buf: &mut [u8], input: T) -> Result<usize, Error> {
+    let mut writer = sink::SliceWriter::new(buf);
+    match to_fmt_writer(&mut writer, input) {
+        Ok(()) => Ok(writer.len()),
+        Err(_) if writer.is_full() => Err(Error::buffer_full()),
+        Err(e) => Err(e),
+    }
 }
 
-/// Struct variant serializer.
+/// Formats a value as `application/x-www-form-urlencoded`.
 ///
-/// Never instantiated, struct variants are not supported.
-pub struct StructVariantSerializer<'input, 'output, T: UrlEncodedTarget> {
-    inner: ser::Impossible<&'output mut UrlEncodedSerializer<'input, T>, Error>,
+/// Returned by [`display`].
+#[derive(Clone, Copy, Debug)]
+pub struct Display<'a, T: ?Sized> {
+    input: &'a T,
 }
 
-impl<'input, 'output, Target> ser::Serializer for Serializer<'input, 'output, Target>
-where
-    Target: 'output + UrlEncodedTarget,
-{
-    type Ok = &'output mut UrlEncodedSerializer<'input, Target>;
-    type Error = Error;
-    type SerializeSeq = SeqSerializer<'input, 'output, Target>;
-    type SerializeTuple = TupleSerializer<'input, 'output, Target>;
-    type SerializeTupleStruct = TupleStructSerializer<'input, 'output, Target>;
-    type SerializeTupleVariant = TupleVariantSerializer<'input, 'output, Target>;
-    type SerializeMap = MapSerializer<'input, 'output, Target>;
-    type SerializeStruct = StructSerializer<'input, 'output, Target>;
-    type SerializeStructVariant = StructVariantSerializer<'input, 'output, Target>;
-
-    /// Returns an error.
-    fn serialize_bool(self, _v: bool) -> Result<Self::Ok, Error> {
-        Err(Error::top_level())
-    }
-
-    /// Returns an error.
-    fn serialize_i8(self, _v: i8) -> Result<Self::Ok, Error> {
-        Err(Error::top_level())
-    }
-
-    /// Returns an error.
-    fn serialize_i16(self, _v: i16) -> Result<Self::Ok, Error> {
-        Err(Error::top_level())
-    }
-
-    /// Returns an error.
-    fn serialize_i32(self, _v: i32) -> Result<Self::Ok, Error> {
-        Err(Error::top_level())
-    }
-
-    /// Returns an error.
-    fn serialize_i64(self, _v: i64) -> Result<Self::Ok, Error> {
-        Err(Error::top_level())
-    }
-
-    /// Returns an error.
-    fn serialize_u8(self, _v: u8) -> Result<Self::Ok, Error> {
-        Err(Error::top_level())
-    }
-
-    /// Returns an error.
-    fn serialize_u16(self, _v: u16) -> Result<Self::Ok, Error> {
-        Err(Error::top_level())
-    }
-
-    /// Returns an error.
-    fn serialize_u32(self, _v: u32) -> Result<Self::Ok, Error> {
-        Err(Error::top_level())
-    }
-
-    /// Returns an error.
-    fn serialize_u64(self, _v: u64) -> Result<Self::Ok, Error> {
-        Err(Error::top_level())
-    }
-
-    /// Returns an error.
-    fn serialize_f32(self, _v: f32) -> Result<Self::Ok, Error> {
-        Err(Error::top_level())
-    }
-
-    /// Returns an error.
-    fn serialize_f64(self, _v: f64) -> Result<Self::Ok, Error> {
-        Err(Error::top_level())
-    }
-
-    /// Returns an error.
-    fn serialize_char(self, _v: char) -> Result<Self::Ok, Error> {
-        Err(Error::top_level())
-    }
-
-    /// Returns an error.
-    fn serialize_str(self, _value: &str) -> Result<Self::Ok, Error> {
-        Err(Error::top_level())
-    }
-
-    /// Returns an error.
-    fn serialize_bytes(self, _value: &[u8]) -> Result<Self::Ok, Error> {
-        Err(Error::top_level())
-    }
-
-    /// Returns `Ok`.
-    fn serialize_unit(self) -> Result<Self::Ok, Error> {
-        Ok(self.urlencoder)
-    }
-
-    /// Returns `Ok`.
-    fn serialize_unit_struct(self, _name: &'static str) -> Result<Self::Ok, Error> {
-        Ok(self.urlencoder)
-    }
-
-    /// Returns an error.
-    fn serialize_unit_variant(
-        self,
-        _name: &'static str,
-        _variant_index: u32,
-        _variant: &'static str,
-    ) -> Result<Self::Ok, Error> {
-        Err(Error::top_level())
-    }
-
-    /// Serializes the inner value, ignoring the newtype name.
-    fn serialize_newtype_struct<T: ?Sized + ser::Serialize>(
-        self,
-        _name: &'static str,
-        value: &T,
-    ) -> Result<Self::Ok, Error> {
-        value.serialize(self)
-    }
-
-    /// Returns an error.
-    fn serialize_newtype_variant<T: ?Sized + ser::Serialize>(
-        self,
-        _name: &'static str,
-        _variant_index: u32,
-        _variant: &'static str,
-        _value: &T,
-    ) -> Result<Self::Ok, Error> {
-        Err(Error::top_level())
-    }
-
-    /// Returns `Ok`.
-    fn serialize_none(self) -> Result<Self::Ok, Error> {
-        Ok(self.urlencoder)
-    }
-
-    /// Serializes the given value.
-    fn serialize_some<T: ?Sized + ser::Serialize>(self, value: &T) -> Result<Self::Ok, Error> {
-        value.serialize(self)
-    }
-
-    /// Serialize a sequence, given length (if any) is ignored.
-    fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq, Error> {
-        Ok(SeqSerializer { urlencoder: self.urlencoder })
-    }
-
-    /// Returns an error.
-    fn serialize_tuple(self, _len: usize) -> Result<Self::SerializeTuple, Error> {
-        Ok(TupleSerializer { urlencoder: self.urlencoder })
-    }
-
-    /// Returns an error.
-    fn serialize_tuple_struct(
-        self,
-        _name: &'static str,
-        _len: usize,
-    ) -> Result<Self::SerializeTupleStruct, Error> {
-        Err(Error::top_level())
-    }
-
-    /// Returns an error.
-    fn serialize_tuple_variant(
-        self,
-        _name: &'static str,
-        _variant_index: u32,
-        _variant: &'static str,
-        _len: usize,
-    ) -> Result<Self::SerializeTupleVariant, Error> {
-        Err(Error::top_level())
-    }
-
-    /// Serializes a map, given length is ignored.
-    fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap, Error> {
-        Ok(MapSerializer { urlencoder: self.urlencoder, key: None })
-    }
-
-    /// Serializes a struct, given length is ignored.
-    fn serialize_struct(
-        self,
-        _name: &'static str,
-        _len: usize,
-    ) -> Result<Self::SerializeStruct, Error> {
-        Ok(StructSerializer { urlencoder: self.urlencoder })
-    }
-
-    /// Returns an error.
-    fn serialize_struct_variant(
-        self,
-        _name: &'static str,
-        _variant_index: u32,
-        _variant: &'static str,
-        _len: usize,
-    ) -> Result<Self::SerializeStructVariant, Error> {
-        Err(Error::top_level())
+impl<T: ?Sized + ser::Serialize> fmt::Display for Display<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        to_fmt_writer(f, self.input).map_err(|_| fmt::Error)
     }
 }
 
-impl<'input, 'output, Target> ser::SerializeSeq for SeqSerializer<'input, 'output, Target>
-where
-    Target: 'output + UrlEncodedTarget,
-{
-    type Ok = &'output mut UrlEncodedSerializer<'input, Target>;
-    type Error = Error;
-
-    fn serialize_element<T: ?Sized + ser::Serialize>(&mut self, value: &T) -> Result<(), Error> {
-        value.serialize(pair::PairSerializer::new(self.urlencoder))
-    }
-
-    fn end(self) -> Result<Self::Ok, Error> {
-        Ok(self.urlencoder)
-    }
+/// Serializes a value into a `application/x-www-form-urlencoded`
+/// [`BytesMut`](bytes::BytesMut).
+///
+/// ```
+/// let meal = &[("bread", "baguette"), ("cheese", "comté")];
+///
+/// let body = serde_html_form::ser::to_bytes_mut(meal).unwrap();
+/// assert_eq!(&body[..], b"bread=baguette&cheese=comt%C3%A9");
+/// ```
+#[cfg(feature = "bytes")]
+pub fn to_bytes_mut<T: ser::Serialize>(input: T) -> Result<bytes::BytesMut, Error> {
+    let mut target = bytes::BytesMut::new();
+    push_to_bytes_mut(&mut target, input)?;
+    Ok(target)
 }
 
-impl<'input, 'output, Target> ser::SerializeTuple for TupleSerializer<'input, 'output, Target>
-where
-    Target: 'output + UrlEncodedTarget,
-{
-    type Ok = &'output mut UrlEncodedSerializer<'input, Target>;
-    type Error = Error;
-
-    fn serialize_element<T: ?Sized + ser::Serialize>(&mut self, value: &T) -> Result<(), Error> {
-        value.serialize(pair::PairSerializer::new(self.urlencoder))
-    }
-
-    fn end(self) -> Result<Self::Ok, Error> {
-        Ok(self.urlencoder)
-    }
+/// Serializes a value into the provided `application/x-www-form-urlencoded`
+/// [`BytesMut`](bytes::BytesMut) buffer.
+///
+/// Like [`push_to_string`], no `&` is written before the first pair.
+#[cfg(feature = "bytes")]
+pub fn push_to_bytes_mut<T: ser::Serialize>(
+    target: &mut bytes::BytesMut,
+    input: T,
+) -> Result<(), Error> {
+    to_fmt_writer(target, input)
 }
 
-impl<'input, 'output, Target> ser::SerializeTupleStruct
-    for TupleStructSerializer<'input, 'output, Target>
-where
-    Target: 'output + UrlEncodedTarget,
-{
-    type Ok = &'output mut UrlEncodedSerializer<'input, Target>;
-    type Error = Error;
-
-    fn serialize_field<T: ?Sized + ser::Serialize>(&mut self, value: &T) -> Result<(), Error> {
-        self.inner.serialize_field(value)
-    }
-
-    fn end(self) -> Result<Self::Ok, Error> {
-        self.inner.end()
-    }
+/// Serializes a value into key-value pairs, without percent-encoding.
+///
+/// The pairs are the same that [`to_string`] encodes, for clients that take
+/// pairs instead of an encoded string. Values that are bytes have to be valid
+/// UTF-8. To serialize nested structs and maps or into other collections, use
+/// a [`pairs::Serializer`] with a [`PairSink`].
+///
+/// ```
+/// use serde::Serialize;
+///
+/// #[derive(Serialize)]
+/// struct Search {
+///     q: &'static str,
+///     page: Option<u32>,
+///     tag: Vec<&'static str>,
+/// }
+///
+/// let search = Search { q: "rust & serde", page: None, tag: vec!["web", "forms"] };
+/// assert_eq!(
+///     serde_html_form::ser::to_pairs(&search).unwrap(),
+///     [("q", "rust & serde"), ("tag", "web"), ("tag", "forms")]
+///         .map(|(k, v)| (k.to_owned(), v.to_owned())),
+/// );
+/// ```
+#[cfg(feature = "alloc")]
+pub fn to_pairs<T: ser::Serialize>(input: T) -> Result<Vec<(String, String)>, Error> {
+    let mut pairs = Vec::new();
+    input.serialize(pairs::Serializer::new(&mut pairs))?;
+    Ok(pairs)
 }
 
-impl<'input, 'output, Target> ser::SerializeTupleVariant
-    for TupleVariantSerializer<'input, 'output, Target>
-where
-    Target: 'output + UrlEncodedTarget,
-{
-    type Ok = &'output mut UrlEncodedSerializer<'input, Target>;
-    type Error = Error;
-
-    fn serialize_field<T: ?Sized + ser::Serialize>(&mut self, value: &T) -> Result<(), Error> {
-        self.inner.serialize_field(value)
-    }
-
-    fn end(self) -> Result<Self::Ok, Error> {
-        self.inner.end()
-    }
+/// Serializes a value into a `application/x-www-form-urlencoded` `String`
+/// buffer, with names and values encoded in the given encoding before
+/// percent-encoding.
+///
+/// Characters that cannot be encoded are written as HTML numeric character
+/// references like `&#12354;`, which is what browsers do. Encodings that
+/// browsers never submit forms in, like UTF-16, are replaced by UTF-8.
+///
+/// ```
+/// let meal = &[("cheese", "comté")];
+///
+/// assert_eq!(
+///     serde_html_form::ser::to_string_with_encoding(meal, encoding_rs::WINDOWS_1252),
+///     Ok("cheese=comt%E9".to_owned())
+/// );
+/// ```
+#[cfg(feature = "encoding")]
+pub fn to_string_with_encoding<T: ser::Serialize>(
+    input: T,
+    encoding: &'static encoding_rs::Encoding,
+) -> Result<String, Error> {
+    let mut target = String::new();
+    push_to_string_with_encoding(&mut target, input, encoding)?;
+    Ok(target)
 }
 
-impl<'input, 'output, Target> ser::SerializeMap for MapSerializer<'input, 'output, Target>
-where
-    Target: 'output + UrlEncodedTarget,
-{
-    type Ok = &'output mut UrlEncodedSerializer<'input, Target>;
-    type Error = Error;
-
-    fn serialize_entry<K: ?Sized + ser::Serialize, V: ?Sized + ser::Serialize>(
-        &mut self,
-        key: &K,
-        value: &V,
-    ) -> Result<(), Error> {
-        let key_sink = key::KeySink::new(|key| {
-            let value_sink = value::ValueSink::new(self.urlencoder, &key);
-            value.serialize(part::PartSerializer::new(value_sink))?;
-            self.key = None;
-            Ok(())
-        });
-        let entry_serializer = part::PartSerializer::new(key_sink);
-        key.serialize(entry_serializer)
-    }
-
-    fn serialize_key<T: ?Sized + ser::Serialize>(&mut self, key: &T) -> Result<(), Error> {
-        let key_sink = key::KeySink::new(|key| Ok(key.into()));
-        let key_serializer = part::PartSerializer::new(key_sink);
-        self.key = Some(key.serialize(key_serializer)?);
-        Ok(())
-    }
+/// Serializes a value into the provided `application/x-www-form-urlencoded`
+/// `String` buffer, with names and values encoded in the given encoding
+/// before percent-encoding.
+///
+/// See [`to_string_with_encoding`] for details.
+#[cfg(feature = "encoding")]
+pub fn push_to_string_with_encoding<T: ser::Serialize>(
+    target: &mut String,
+    input: T,
+    encoding: &'static encoding_rs::Encoding,
+) -> Result<(), Error> {
+    let mut encoder = Encoder::new(target);
+    encoder.encoding(encoding);
+    input.serialize(pairs::Serializer::new(&mut encoder))?;
+    Ok(())
+}
 
-    fn serialize_value<T: ?Sized + ser::Serialize>(&mut self, value: &T) -> Result<(), Error> {
-        {
-            let key = self.key.as_ref().ok_or_else(Error::no_key)?;
-            let value_sink = value::ValueSink::new(self.urlencoder, key);
-            value.serialize(part::PartSerializer::new(value_sink))?;
-        }
-        self.key = None;
-        Ok(())
-    }
+/// A serializer for the `application/x-www-form-urlencoded` format that
+/// writes to a `form_urlencoded::Serializer`.
+///
+/// This is a [`pairs::Serializer`], which can write to other [`PairSink`]s
+/// as well.
+#[cfg(feature = "alloc")]
+pub type Serializer<'input, 'output, Target> =
+    pairs::Serializer<'output, UrlEncodedSerializer<'input, Target>>;
 
-    fn end(self) -> Result<Self::Ok, Error> {
-        Ok(self.urlencoder)
-    }
-}
+/// Sequence serializer.
+#[cfg(feature = "alloc")]
+pub type SeqSerializer<'input, 'output, Target> =
+    pairs::SeqSerializer<'output, UrlEncodedSerializer<'input, Target>>;
 
-impl<'input, 'output, Target> ser::SerializeStruct for StructSerializer<'input, 'output, Target>
-where
-    Target: 'output + UrlEncodedTarget,
-{
-    type Ok = &'output mut UrlEncodedSerializer<'input, Target>;
-    type Error = Error;
+/// Tuple serializer.
+#[cfg(feature = "alloc")]
+pub type TupleSerializer<'input, 'output, Target> =
+    pairs::TupleSerializer<'output, UrlEncodedSerializer<'input, Target>>;
 
-    fn serialize_field<T: ?Sized + ser::Serialize>(
-        &mut self,
-        key: &'static str,
-        value: &T,
-    ) -> Result<(), Error> {
-        let value_sink = value::ValueSink::new(self.urlencoder, key);
-        value.serialize(part::PartSerializer::new(value_sink))
-    }
+/// Tuple struct serializer.
+#[cfg(feature = "alloc")]
+pub type TupleStructSerializer<'input, 'output, Target> =
+    pairs::TupleStructSerializer<'output, UrlEncodedSerializer<'input, Target>>;
 
-    fn end(self) -> Result<Self::Ok, Error> {
-        Ok(self.urlencoder)
-    }
-}
+/// Tuple variant serializer.
+#[cfg(feature = "alloc")]
+pub type TupleVariantSerializer<'input, 'output, Target> =
+    pairs::TupleVariantSerializer<'output, UrlEncodedSerializer<'input, Target>>;
 
-impl<'input, 'output, Target> ser::SerializeStructVariant
-    for StructVariantSerializer<'input, 'output, Target>
-where
-    Target: 'output + UrlEncodedTarget,
-{
-    type Ok = &'output mut UrlEncodedSerializer<'input, Target>;
-    type Error = Error;
+/// Map serializer.
+#[cfg(feature = "alloc")]
+pub type MapSerializer<'input, 'output, Target> =
+    pairs::MapSerializer<'output, UrlEncodedSerializer<'input, Target>>;
 
-    fn serialize_field<T: ?Sized + ser::Serialize>(
-        &mut self,
-        key: &'static str,
-        value: &T,
-    ) -> Result<(), Error> {
-        self.inner.serialize_field(key, value)
-    }
+/// Struct serializer.
+#[cfg(feature = "alloc")]
+pub type StructSerializer<'input, 'output, Target> =
+    pairs::StructSerializer<'output, UrlEncodedSerializer<'input, Target>>;
 
-    fn end(self) -> Result<Self::Ok, Error> {
-        self.inner.end()
-    }
-}
+/// Struct variant serializer.
+#[cfg(feature = "alloc")]
+pub type StructVariantSerializer<'input, 'output, Target> =
+    pairs::StructVariantSerializer<'output, UrlEncodedSerializer<'input, Target>>;
 
-#[cfg(test)]
+#[cfg(all(test, feature = "alloc"))]
 mod tests;
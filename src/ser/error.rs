@@ -1,85 +1,264 @@
-use alloc::{borrow::Cow, format};
+#[cfg(feature = "std")]
+use alloc::sync::Arc;
+#[cfg(feature = "alloc")]
+use alloc::{borrow::Cow, format, string::String};
 use core::{
     fmt,
     str::{self, Utf8Error},
 };
-use std::error;
+#[cfg(feature = "std")]
+use std::{error, io};
 
 use serde_core::ser;
 
 /// Errors returned during serializing to `application/x-www-form-urlencoded`.
+///
+/// Besides the message, an error carries its [`ErrorKind`] and the key of the
+/// pair that was being serialized when it occurred, if any.
+#[cfg_attr(feature = "alloc", doc = "```")]
+#[cfg_attr(not(feature = "alloc"), doc = "```ignore")]
+/// use serde::Serialize;
+/// use serde_html_form::ser::ErrorKind;
+///
+/// #[derive(Serialize)]
+/// struct Form {
+///     name: &'static str,
+///     tags: Vec<Vec<&'static str>>,
+/// }
+///
+/// let form = Form { name: "Jane", tags: vec![vec!["a"]] };
+/// let error = serde_html_form::to_string(form).unwrap_err();
+/// assert_eq!(error.kind(), ErrorKind::UnsupportedValue);
+/// assert_eq!(error.path(), Some("tags"));
+/// assert_eq!(error.to_string(), "unsupported value");
+/// ```
 #[derive(Clone, Debug, PartialEq, Eq)]
-pub struct Error(ErrorKind);
+pub struct Error {
+    kind: ErrorKind,
+    repr: Repr,
+    #[cfg(feature = "alloc")]
+    path: Option<String>,
+}
 
-#[derive(Clone, Debug, PartialEq, Eq)]
-enum ErrorKind {
-    Custom(Cow<'static, str>),
+/// The category of a serialization [`Error`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ErrorKind {
+    /// The value is not a map, a struct or a sequence of pairs.
+    TopLevel,
+
+    /// A key is not a string, a number, a boolean or a unit enum variant, or
+    /// a key of a nested map is empty or contains a separator of the key
+    /// notation.
+    UnsupportedKey,
+
+    /// A value can not be written as the value of a pair, like a nested
+    /// sequence or a struct with flat keys.
+    UnsupportedValue,
+
+    /// An element of a sequence of pairs is not a pair.
+    UnsupportedPair,
+
+    /// Bytes that are not valid UTF-8 were written to an output that only
+    /// supports strings.
+    InvalidUtf8,
+
+    /// Writing to the output failed, like an I/O error of
+    /// `to_writer`.
+    Write,
+
+    /// The buffer given to [`to_slice`](super::to_slice) is too small.
+    BufferFull,
+
+    /// Without the `alloc` feature, a value formatted with `Display` is longer
+    /// than 400 bytes, or a key that has to be kept until its value is
+    /// serialized is longer than 128 bytes.
+    TooLong,
+
+    /// Any other error, like a custom error from a `Serialize`
+    /// implementation.
+    Custom,
+}
+
+#[cfg(feature = "alloc")]
+type Message = Cow<'static, str>;
+
+/// Without an allocator, custom messages are not kept.
+#[cfg(not(feature = "alloc"))]
+type Message = &'static str;
+
+#[derive(Clone, Debug)]
+enum Repr {
+    Message(Message),
     Utf8(Utf8Error),
+    #[cfg(feature = "std")]
+    Io(Arc<io::Error>),
+}
+
+/// I/O errors are only equal to themselves.
+impl PartialEq for Repr {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Repr::Message(a), Repr::Message(b)) => a == b,
+            (Repr::Utf8(a), Repr::Utf8(b)) => a == b,
+            #[cfg(feature = "std")]
+            (Repr::Io(a), Repr::Io(b)) => Arc::ptr_eq(a, b),
+            _ => false,
+        }
+    }
 }
 
+impl Eq for Repr {}
+
 impl Error {
+    // `Message` is a plain `&'static str` without `alloc`.
+    #[cfg_attr(not(feature = "alloc"), allow(clippy::useless_conversion))]
+    fn new(kind: ErrorKind, message: &'static str) -> Self {
+        Error::with_repr(kind, Repr::Message(message.into()))
+    }
+
+    fn with_repr(kind: ErrorKind, repr: Repr) -> Self {
+        Error {
+            kind,
+            repr,
+            #[cfg(feature = "alloc")]
+            path: None,
+        }
+    }
+
     pub(super) fn done() -> Self {
-        Error(ErrorKind::Custom("this pair has already been serialized".into()))
+        Error::new(ErrorKind::UnsupportedPair, "this pair has already been serialized")
     }
 
     pub(super) fn not_done() -> Self {
-        Error(ErrorKind::Custom("this pair has not yet been serialized".into()))
+        Error::new(ErrorKind::UnsupportedPair, "this pair has not yet been serialized")
     }
 
     pub(super) fn unsupported_key() -> Self {
-        Error(ErrorKind::Custom("unsupported key".into()))
+        Error::new(ErrorKind::UnsupportedKey, "unsupported key")
     }
 
     pub(super) fn unsupported_value() -> Self {
-        Error(ErrorKind::Custom("unsupported value".into()))
+        Error::new(ErrorKind::UnsupportedValue, "unsupported value")
     }
 
     pub(super) fn unsupported_pair() -> Self {
-        Error(ErrorKind::Custom("unsupported pair".into()))
+        Error::new(ErrorKind::UnsupportedPair, "unsupported pair")
     }
 
     pub(super) fn top_level() -> Self {
-        Error(ErrorKind::Custom("top-level serializer supports only maps and structs".into()))
+        Error::new(ErrorKind::TopLevel, "top-level serializer supports only maps and structs")
     }
 
     pub(super) fn no_key() -> Self {
-        Error(ErrorKind::Custom("tried to serialize a value before serializing key".into()))
+        Error::new(ErrorKind::Custom, "tried to serialize a value before serializing key")
     }
 
     pub(super) fn utf8(error: Utf8Error) -> Self {
-        Error(ErrorKind::Utf8(error))
+        Error::with_repr(ErrorKind::InvalidUtf8, Repr::Utf8(error))
+    }
+
+    pub(super) fn write() -> Self {
+        Error::new(ErrorKind::Write, "failed to write output")
+    }
+
+    #[cfg(feature = "std")]
+    pub(super) fn io(error: io::Error) -> Self {
+        Error::with_repr(ErrorKind::Write, Repr::Io(Arc::new(error)))
+    }
+
+    pub(super) fn buffer_full() -> Self {
+        Error::new(ErrorKind::BufferFull, "buffer full")
+    }
+
+    #[cfg(not(feature = "alloc"))]
+    pub(super) fn key_too_long() -> Self {
+        Error::new(ErrorKind::TooLong, "key too long to keep without the `alloc` feature")
+    }
+
+    #[cfg(any(not(feature = "ryu"), not(feature = "alloc")))]
+    pub(super) fn value_too_long() -> Self {
+        Error::new(ErrorKind::TooLong, "value too long to format without the `alloc` feature")
+    }
+
+    /// Sets the path of the error, unless it already has one.
+    #[cfg(feature = "alloc")]
+    pub(super) fn with_path(mut self, path: &str) -> Self {
+        if self.path.is_none() {
+            self.path = Some(path.into());
+        }
+        self
+    }
+
+    #[cfg(not(feature = "alloc"))]
+    pub(super) fn with_path(self, _path: &str) -> Self {
+        self
+    }
+
+    /// Returns the category of this error.
+    pub fn kind(&self) -> ErrorKind {
+        self.kind
+    }
+
+    /// Returns the key of the pair that was being serialized when the error
+    /// occurred, like the name of a struct field or a map key.
+    ///
+    /// For nested keys, this is the full key, like `address[city]`. Without
+    /// the `alloc` feature, this is always `None`.
+    pub fn path(&self) -> Option<&str> {
+        #[cfg(feature = "alloc")]
+        return self.path.as_deref();
+
+        #[cfg(not(feature = "alloc"))]
+        return None;
     }
 }
 
 impl fmt::Display for Error {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        match &self.0 {
-            ErrorKind::Custom(msg) => msg.fmt(f),
-            ErrorKind::Utf8(err) => write!(f, "invalid UTF-8: {}", err),
+        match &self.repr {
+            Repr::Message(msg) => msg.fmt(f),
+            Repr::Utf8(err) => write!(f, "invalid UTF-8: {}", err),
+            #[cfg(feature = "std")]
+            Repr::Io(err) => write!(f, "failed to write output: {}", err),
         }
     }
 }
 
+#[cfg(feature = "std")]
 impl error::Error for Error {
-    /// The lower-level cause of this error, in the case of a `Utf8` error.
+    /// The lower-level cause of this error, in the case of a `Utf8` or I/O
+    /// error.
     fn cause(&self) -> Option<&dyn error::Error> {
-        match &self.0 {
-            ErrorKind::Custom(_) => None,
-            ErrorKind::Utf8(err) => Some(err),
+        match &self.repr {
+            Repr::Message(_) => None,
+            Repr::Utf8(err) => Some(err),
+            Repr::Io(err) => Some(&**err),
         }
     }
 
-    /// The lower-level source of this error, in the case of a `Utf8` error.
+    /// The lower-level source of this error, in the case of a `Utf8` or I/O
+    /// error.
     fn source(&self) -> Option<&(dyn error::Error + 'static)> {
-        match &self.0 {
-            ErrorKind::Custom(_) => None,
-            ErrorKind::Utf8(err) => Some(err),
+        match &self.repr {
+            Repr::Message(_) => None,
+            Repr::Utf8(err) => Some(err),
+            Repr::Io(err) => Some(&**err),
         }
     }
 }
 
+#[cfg(not(feature = "std"))]
+impl ser::StdError for Error {}
+
 impl ser::Error for Error {
+    #[cfg(feature = "alloc")]
     fn custom<T: fmt::Display>(msg: T) -> Self {
-        Self(ErrorKind::Custom(format!("{}", msg).into()))
+        Error::with_repr(ErrorKind::Custom, Repr::Message(format!("{}", msg).into()))
+    }
+
+    #[cfg(not(feature = "alloc"))]
+    fn custom<T: fmt::Display>(_msg: T) -> Self {
+        Error::new(ErrorKind::Custom, "custom error")
     }
 }
@@ -1,5 +1,6 @@
-use alloc::{borrow::Cow, string::String};
-use core::ops::Deref;
+#[cfg(feature = "alloc")]
+use alloc::string::String;
+use core::{ops::Deref, str};
 
 use serde_core::ser::{self, Serialize};
 
@@ -7,7 +8,7 @@ use super::{part::Sink, Error};
 
 pub enum Key<'key> {
     Static(&'static str),
-    Dynamic(Cow<'key, str>),
+    Dynamic(&'key str),
 }
 
 impl Deref for Key<'_> {
@@ -16,16 +17,59 @@ impl Deref for Key<'_> {
     fn deref(&self) -> &str {
         match *self {
             Key::Static(key) => key,
-            Key::Dynamic(ref key) => key,
+            Key::Dynamic(key) => key,
         }
     }
 }
 
-impl<'key> From<Key<'key>> for Cow<'static, str> {
-    fn from(key: Key<'key>) -> Self {
-        match key {
-            Key::Static(key) => key.into(),
-            Key::Dynamic(key) => key.into_owned().into(),
+impl Key<'_> {
+    /// Keeps the key until its value is serialized.
+    pub fn into_owned(self) -> Result<OwnedKey, Error> {
+        match self {
+            Key::Static(key) => Ok(OwnedKey::Static(key)),
+            #[cfg(feature = "alloc")]
+            Key::Dynamic(key) => Ok(OwnedKey::Owned(key.into())),
+            #[cfg(not(feature = "alloc"))]
+            Key::Dynamic(key) => {
+                let mut buf = [0; INLINE_KEY_LEN];
+                buf.get_mut(..key.len())
+                    .ok_or_else(Error::key_too_long)?
+                    .copy_from_slice(key.as_bytes());
+                Ok(OwnedKey::Inline { buf, len: key.len() })
+            }
+        }
+    }
+}
+
+/// The longest key that is kept between separate calls for a key and its
+/// value without the `alloc` feature.
+#[cfg(not(feature = "alloc"))]
+const INLINE_KEY_LEN: usize = 128;
+
+/// A key that is kept until its value is serialized, for
+/// `SerializeMap::serialize_key` and pairs.
+pub enum OwnedKey {
+    Static(&'static str),
+    #[cfg(feature = "alloc")]
+    Owned(String),
+    #[cfg(not(feature = "alloc"))]
+    Inline {
+        buf: [u8; INLINE_KEY_LEN],
+        len: usize,
+    },
+}
+
+impl Deref for OwnedKey {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        match self {
+            OwnedKey::Static(key) => key,
+            #[cfg(feature = "alloc")]
+            OwnedKey::Owned(key) => key,
+            // The buffer holds a copy of a `str`.
+            #[cfg(not(feature = "alloc"))]
+            OwnedKey::Inline { buf, len } => str::from_utf8(&buf[..*len]).unwrap_or_default(),
         }
     }
 }
@@ -49,17 +93,15 @@ where
 {
     type Ok = Ok;
     type SerializeSeq = ser::Impossible<Self::Ok, Error>;
+    type SerializeMap = ser::Impossible<Self::Ok, Error>;
+    type SerializeStruct = ser::Impossible<Self::Ok, Error>;
 
     fn serialize_static_str(self, value: &'static str) -> Result<Ok, Error> {
         (self.end)(Key::Static(value))
     }
 
     fn serialize_str(self, value: &str) -> Result<Ok, Error> {
-        (self.end)(Key::Dynamic(value.into()))
-    }
-
-    fn serialize_string(self, value: String) -> Result<Ok, Error> {
-        (self.end)(Key::Dynamic(value.into()))
+        (self.end)(Key::Dynamic(value))
     }
 
     fn serialize_none(self) -> Result<Ok, Error> {
@@ -74,6 +116,14 @@ where
         Err(self.unsupported())
     }
 
+    fn serialize_map(self) -> Result<Self::SerializeMap, Error> {
+        Err(self.unsupported())
+    }
+
+    fn serialize_struct(self) -> Result<Self::SerializeStruct, Error> {
+        Err(self.unsupported())
+    }
+
     fn unsupported(self) -> Error {
         Error::unsupported_key()
     }
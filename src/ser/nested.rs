@@ -0,0 +1,145 @@
+use alloc::{borrow::Cow, string::String};
+
+use serde_core::ser;
+
+use super::{key::KeySink, part::PartSerializer, sink::PairSink, value::ValueSink, Error};
+use crate::KeyNotation;
+
+/// Serializer for structs and maps nested in a field or map value, writing
+/// their entries with keys like `prefix[key]`.
+pub struct NestedSerializer<'prefix, 'target, Target: PairSink> {
+    sink: &'target mut Target,
+    prefix: Cow<'prefix, str>,
+    key_notation: KeyNotation,
+    key: Option<String>,
+}
+
+impl<'prefix, 'target, Target> NestedSerializer<'prefix, 'target, Target>
+where
+    Target: 'target + PairSink,
+{
+    pub fn new(
+        sink: &'target mut Target,
+        prefix: Cow<'prefix, str>,
+        key_notation: KeyNotation,
+    ) -> Self {
+        NestedSerializer { sink, prefix, key_notation, key: None }
+    }
+
+    fn nested_key(&self, key: &str) -> Result<String, Error> {
+        nested_key(&self.prefix, key, self.key_notation)
+    }
+
+    fn append_value<T: ?Sized + ser::Serialize>(
+        &mut self,
+        key: &str,
+        value: &T,
+    ) -> Result<(), Error> {
+        let value_sink = ValueSink::new(self.sink, key, self.key_notation);
+        value.serialize(PartSerializer::new(value_sink)).map_err(|e| e.with_path(key))
+    }
+}
+
+/// Appends the path segment `key` to `prefix`.
+///
+/// Segments that contain the separators of the notation are rejected, because
+/// they would not be read back as a single segment, and so are empty segments,
+/// which would be read back as the next element of a sequence.
+pub(super) fn nested_key(
+    prefix: &str,
+    key: &str,
+    key_notation: KeyNotation,
+) -> Result<String, Error> {
+    let mut res = String::with_capacity(prefix.len() + key.len() + 2);
+    res.push_str(prefix);
+    match key_notation {
+        KeyNotation::Flat | KeyNotation::Brackets => {
+            if key.is_empty() || key.contains(&['[', ']'][..]) {
+                return Err(Error::unsupported_key());
+            }
+            res.push('[');
+            res.push_str(key);
+            res.push(']');
+        }
+        KeyNotation::Dots => {
+            if key.is_empty() || key.contains(&['.', '[', ']'][..]) {
+                return Err(Error::unsupported_key());
+            }
+            res.push('.');
+            res.push_str(key);
+        }
+    }
+
+    Ok(res)
+}
+
+/// Appends the sequence index `index` to `prefix`.
+///
+/// Indices are written in brackets in all notations.
+pub(super) fn indexed_key(prefix: &str, index: usize) -> String {
+    let mut buf = itoa::Buffer::new();
+    let index = buf.format(index);
+
+    let mut res = String::with_capacity(prefix.len() + index.len() + 2);
+    res.push_str(prefix);
+    res.push('[');
+    res.push_str(index);
+    res.push(']');
+    res
+}
+
+impl<'target, Target> ser::SerializeStruct for NestedSerializer<'_, 'target, Target>
+where
+    Target: 'target + PairSink,
+{
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_field<T: ?Sized + ser::Serialize>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), Error> {
+        let key = self.nested_key(key)?;
+        self.append_value(&key, value)
+    }
+
+    fn end(self) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+impl<'target, Target> ser::SerializeMap for NestedSerializer<'_, 'target, Target>
+where
+    Target: 'target + PairSink,
+{
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_entry<K: ?Sized + ser::Serialize, V: ?Sized + ser::Serialize>(
+        &mut self,
+        key: &K,
+        value: &V,
+    ) -> Result<(), Error> {
+        let key_sink = KeySink::new(|key| {
+            let key = self.nested_key(&key)?;
+            self.append_value(&key, value)
+        });
+        key.serialize(PartSerializer::new(key_sink))
+    }
+
+    fn serialize_key<T: ?Sized + ser::Serialize>(&mut self, key: &T) -> Result<(), Error> {
+        let key_sink = KeySink::new(|key| self.nested_key(&key));
+        self.key = Some(key.serialize(PartSerializer::new(key_sink))?);
+        Ok(())
+    }
+
+    fn serialize_value<T: ?Sized + ser::Serialize>(&mut self, value: &T) -> Result<(), Error> {
+        let key = self.key.take().ok_or_else(Error::no_key)?;
+        self.append_value(&key, value)
+    }
+
+    fn end(self) -> Result<(), Error> {
+        Ok(())
+    }
+}
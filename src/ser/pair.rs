@@ -1,28 +1,36 @@
-use alloc::borrow::Cow;
 use core::mem;
 
-use form_urlencoded::{Serializer as UrlEncodedSerializer, Target as UrlEncodedTarget};
 use serde_core::ser;
 
-use crate::ser::{key::KeySink, part::PartSerializer, value::ValueSink, Error};
-
-pub struct PairSerializer<'input, 'target, Target: UrlEncodedTarget> {
-    urlencoder: &'target mut UrlEncodedSerializer<'input, Target>,
+use crate::{
+    ser::{
+        key::{KeySink, OwnedKey},
+        part::PartSerializer,
+        sink::PairSink,
+        value::ValueSink,
+        Error,
+    },
+    KeyNotation,
+};
+
+pub struct PairSerializer<'target, Target: PairSink> {
+    sink: &'target mut Target,
+    key_notation: KeyNotation,
     state: PairState,
 }
 
-impl<'input, 'target, Target> PairSerializer<'input, 'target, Target>
+impl<'target, Target> PairSerializer<'target, Target>
 where
-    Target: 'target + UrlEncodedTarget,
+    Target: 'target + PairSink,
 {
-    pub fn new(urlencoder: &'target mut UrlEncodedSerializer<'input, Target>) -> Self {
-        PairSerializer { urlencoder, state: PairState::WaitingForKey }
+    pub fn new(sink: &'target mut Target, key_notation: KeyNotation) -> Self {
+        PairSerializer { sink, key_notation, state: PairState::WaitingForKey }
     }
 }
 
-impl<'target, Target> ser::Serializer for PairSerializer<'_, 'target, Target>
+impl<'target, Target> ser::Serializer for PairSerializer<'target, Target>
 where
-    Target: 'target + UrlEncodedTarget,
+    Target: 'target + PairSink,
 {
     type Ok = ();
     type Error = Error;
@@ -38,6 +46,11 @@ where
         Err(Error::unsupported_pair())
     }
 
+    #[cfg(not(feature = "alloc"))]
+    fn collect_str<T: ?Sized + core::fmt::Display>(self, _value: &T) -> Result<(), Error> {
+        Err(Error::unsupported_pair())
+    }
+
     fn serialize_i8(self, _v: i8) -> Result<(), Error> {
         Err(Error::unsupported_pair())
     }
@@ -186,9 +199,9 @@ where
     }
 }
 
-impl<'target, Target> ser::SerializeTuple for PairSerializer<'_, 'target, Target>
+impl<'target, Target> ser::SerializeTuple for PairSerializer<'target, Target>
 where
-    Target: 'target + UrlEncodedTarget,
+    Target: 'target + PairSink,
 {
     type Ok = ();
     type Error = Error;
@@ -196,16 +209,16 @@ where
     fn serialize_element<T: ?Sized + ser::Serialize>(&mut self, value: &T) -> Result<(), Error> {
         match mem::replace(&mut self.state, PairState::Done) {
             PairState::WaitingForKey => {
-                let key_sink = KeySink::new(|key| Ok(key.into()));
+                let key_sink = KeySink::new(|key| key.into_owned());
                 let key_serializer = PartSerializer::new(key_sink);
                 self.state = PairState::WaitingForValue { key: value.serialize(key_serializer)? };
                 Ok(())
             }
             PairState::WaitingForValue { key } => {
                 let result = {
-                    let value_sink = ValueSink::new(self.urlencoder, &key);
+                    let value_sink = ValueSink::new(self.sink, &key, self.key_notation);
                     let value_serializer = PartSerializer::new(value_sink);
-                    value.serialize(value_serializer)
+                    value.serialize(value_serializer).map_err(|e| e.with_path(&key))
                 };
                 if result.is_ok() {
                     self.state = PairState::Done;
@@ -229,6 +242,6 @@ where
 
 enum PairState {
     WaitingForKey,
-    WaitingForValue { key: Cow<'static, str> },
+    WaitingForValue { key: OwnedKey },
     Done,
 }
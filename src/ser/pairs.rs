@@ -0,0 +1,478 @@
+//! A serializer that writes pairs to any [`PairSink`].
+
+#[cfg(not(feature = "alloc"))]
+use core::fmt;
+
+use serde_core::ser;
+
+#[cfg(doc)]
+use super::ErrorKind;
+use super::{key, key::OwnedKey, pair, part, value, Error, PairSink};
+use crate::KeyNotation;
+
+/// A serializer for the `application/x-www-form-urlencoded` format that
+/// writes to any [`PairSink`].
+///
+/// * Supported top-level inputs are structs, maps and sequences of pairs,
+///   with or without a given length.
+///
+/// * Supported keys and values are integers, bytes, unit structs and unit
+///   variants. Keys that are bytes have to be valid UTF-8, and so do values
+///   unless the [`PairSink`] supports bytes, which is the case for the
+///   encoder used by `to_string` and [`to_slice`](super::to_slice). There,
+///   bytes are percent-encoded as they are.
+///
+/// * Newtype structs defer to their inner values.
+///
+/// * Nested structs and maps are only supported with a
+///   [`key_notation`][Self::key_notation] other than the default.
+pub struct Serializer<'output, Target: PairSink> {
+    sink: &'output mut Target,
+    key_notation: KeyNotation,
+}
+
+impl<'output, Target: 'output + PairSink> Serializer<'output, Target> {
+    /// Returns a new `Serializer`.
+    ///
+    /// `sink` is usually a `form_urlencoded::Serializer`, or a
+    /// `Vec<(String, String)>` for pairs without percent-encoding.
+    pub fn new(sink: &'output mut Target) -> Self {
+        Serializer { sink, key_notation: KeyNotation::Flat }
+    }
+
+    /// Sets the notation used to write the keys of nested structs and maps.
+    ///
+    /// Nested keys are built at runtime, so they need the `alloc` feature.
+    /// Keys of nested maps that are empty or contain a separator of the
+    /// notation, like `]` with `KeyNotation::Brackets` or `.` with
+    /// `KeyNotation::Dots`, can not be read back and result in an
+    /// [`ErrorKind::UnsupportedKey`] error.
+    #[cfg_attr(feature = "alloc", doc = "```")]
+    #[cfg_attr(not(feature = "alloc"), doc = "```ignore")]
+    /// # use std::collections::BTreeMap;
+    /// use serde::{Serialize, Serializer as _};
+    /// use serde_html_form::{ser::pairs::Serializer, KeyNotation};
+    ///
+    /// #[derive(Serialize)]
+    /// struct Form {
+    ///     address: Address,
+    ///     filters: BTreeMap<&'static str, &'static str>,
+    /// }
+    ///
+    /// #[derive(Serialize)]
+    /// struct Address {
+    ///     city: &'static str,
+    ///     zip: &'static str,
+    /// }
+    ///
+    /// let form = Form {
+    ///     address: Address { city: "Berlin", zip: "10115" },
+    ///     filters: BTreeMap::from([("status", "open")]),
+    /// };
+    ///
+    /// let mut target = String::new();
+    /// let mut urlencoder = form_urlencoded::Serializer::new(&mut target);
+    /// form.serialize(Serializer::new(&mut urlencoder).key_notation(KeyNotation::Brackets)).unwrap();
+    ///
+    /// assert_eq!(target, "address%5Bcity%5D=Berlin&address%5Bzip%5D=10115&filters%5Bstatus%5D=open");
+    /// ```
+    pub fn key_notation(mut self, notation: KeyNotation) -> Self {
+        self.key_notation = notation;
+        self
+    }
+}
+
+/// Sequence serializer.
+pub struct SeqSerializer<'output, Target: PairSink> {
+    sink: &'output mut Target,
+    key_notation: KeyNotation,
+}
+
+/// Tuple serializer.
+///
+/// Mostly used for arrays.
+pub struct TupleSerializer<'output, Target: PairSink> {
+    sink: &'output mut Target,
+    key_notation: KeyNotation,
+}
+
+/// Tuple struct serializer.
+///
+/// Never instantiated, tuple structs are not supported.
+pub struct TupleStructSerializer<'output, T: PairSink> {
+    inner: ser::Impossible<&'output mut T, Error>,
+}
+
+/// Tuple variant serializer.
+///
+/// Never instantiated, tuple variants are not supported.
+pub struct TupleVariantSerializer<'output, T: PairSink> {
+    inner: ser::Impossible<&'output mut T, Error>,
+}
+
+/// Map serializer.
+pub struct MapSerializer<'output, Target: PairSink> {
+    sink: &'output mut Target,
+    key_notation: KeyNotation,
+    key: Option<OwnedKey>,
+}
+
+/// Struct serializer.
+pub struct StructSerializer<'output, Target: PairSink> {
+    sink: &'output mut Target,
+    key_notation: KeyNotation,
+}
+
+/// Struct variant serializer.
+///
+/// Never instantiated, struct variants are not supported.
+pub struct StructVariantSerializer<'output, T: PairSink> {
+    inner: ser::Impossible<&'output mut T, Error>,
+}
+
+impl<'output, Target> ser::Serializer for Serializer<'output, Target>
+where
+    Target: 'output + PairSink,
+{
+    type Ok = &'output mut Target;
+    type Error = Error;
+    type SerializeSeq = SeqSerializer<'output, Target>;
+    type SerializeTuple = TupleSerializer<'output, Target>;
+    type SerializeTupleStruct = TupleStructSerializer<'output, Target>;
+    type SerializeTupleVariant = TupleVariantSerializer<'output, Target>;
+    type SerializeMap = MapSerializer<'output, Target>;
+    type SerializeStruct = StructSerializer<'output, Target>;
+    type SerializeStructVariant = StructVariantSerializer<'output, Target>;
+
+    /// Returns an error.
+    fn serialize_bool(self, _v: bool) -> Result<Self::Ok, Error> {
+        Err(Error::top_level())
+    }
+
+    /// Returns an error.
+    #[cfg(not(feature = "alloc"))]
+    fn collect_str<T: ?Sized + fmt::Display>(self, _value: &T) -> Result<Self::Ok, Error> {
+        Err(Error::top_level())
+    }
+
+    /// Returns an error.
+    fn serialize_i8(self, _v: i8) -> Result<Self::Ok, Error> {
+        Err(Error::top_level())
+    }
+
+    /// Returns an error.
+    fn serialize_i16(self, _v: i16) -> Result<Self::Ok, Error> {
+        Err(Error::top_level())
+    }
+
+    /// Returns an error.
+    fn serialize_i32(self, _v: i32) -> Result<Self::Ok, Error> {
+        Err(Error::top_level())
+    }
+
+    /// Returns an error.
+    fn serialize_i64(self, _v: i64) -> Result<Self::Ok, Error> {
+        Err(Error::top_level())
+    }
+
+    /// Returns an error.
+    fn serialize_u8(self, _v: u8) -> Result<Self::Ok, Error> {
+        Err(Error::top_level())
+    }
+
+    /// Returns an error.
+    fn serialize_u16(self, _v: u16) -> Result<Self::Ok, Error> {
+        Err(Error::top_level())
+    }
+
+    /// Returns an error.
+    fn serialize_u32(self, _v: u32) -> Result<Self::Ok, Error> {
+        Err(Error::top_level())
+    }
+
+    /// Returns an error.
+    fn serialize_u64(self, _v: u64) -> Result<Self::Ok, Error> {
+        Err(Error::top_level())
+    }
+
+    /// Returns an error.
+    fn serialize_f32(self, _v: f32) -> Result<Self::Ok, Error> {
+        Err(Error::top_level())
+    }
+
+    /// Returns an error.
+    fn serialize_f64(self, _v: f64) -> Result<Self::Ok, Error> {
+        Err(Error::top_level())
+    }
+
+    /// Returns an error.
+    fn serialize_char(self, _v: char) -> Result<Self::Ok, Error> {
+        Err(Error::top_level())
+    }
+
+    /// Returns an error.
+    fn serialize_str(self, _value: &str) -> Result<Self::Ok, Error> {
+        Err(Error::top_level())
+    }
+
+    /// Returns an error.
+    fn serialize_bytes(self, _value: &[u8]) -> Result<Self::Ok, Error> {
+        Err(Error::top_level())
+    }
+
+    /// Returns `Ok`.
+    fn serialize_unit(self) -> Result<Self::Ok, Error> {
+        Ok(self.sink)
+    }
+
+    /// Returns `Ok`.
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<Self::Ok, Error> {
+        Ok(self.sink)
+    }
+
+    /// Returns an error.
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+    ) -> Result<Self::Ok, Error> {
+        Err(Error::top_level())
+    }
+
+    /// Serializes the inner value, ignoring the newtype name.
+    fn serialize_newtype_struct<T: ?Sized + ser::Serialize>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<Self::Ok, Error> {
+        value.serialize(self)
+    }
+
+    /// Returns an error.
+    fn serialize_newtype_variant<T: ?Sized + ser::Serialize>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _value: &T,
+    ) -> Result<Self::Ok, Error> {
+        Err(Error::top_level())
+    }
+
+    /// Returns `Ok`.
+    fn serialize_none(self) -> Result<Self::Ok, Error> {
+        Ok(self.sink)
+    }
+
+    /// Serializes the given value.
+    fn serialize_some<T: ?Sized + ser::Serialize>(self, value: &T) -> Result<Self::Ok, Error> {
+        value.serialize(self)
+    }
+
+    /// Serialize a sequence, given length (if any) is ignored.
+    fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq, Error> {
+        Ok(SeqSerializer { sink: self.sink, key_notation: self.key_notation })
+    }
+
+    /// Returns an error.
+    fn serialize_tuple(self, _len: usize) -> Result<Self::SerializeTuple, Error> {
+        Ok(TupleSerializer { sink: self.sink, key_notation: self.key_notation })
+    }
+
+    /// Returns an error.
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleStruct, Error> {
+        Err(Error::top_level())
+    }
+
+    /// Returns an error.
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleVariant, Error> {
+        Err(Error::top_level())
+    }
+
+    /// Serializes a map, given length is ignored.
+    fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap, Error> {
+        Ok(MapSerializer { sink: self.sink, key_notation: self.key_notation, key: None })
+    }
+
+    /// Serializes a struct, given length is ignored.
+    fn serialize_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStruct, Error> {
+        Ok(StructSerializer { sink: self.sink, key_notation: self.key_notation })
+    }
+
+    /// Returns an error.
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStructVariant, Error> {
+        Err(Error::top_level())
+    }
+}
+
+impl<'output, Target> ser::SerializeSeq for SeqSerializer<'output, Target>
+where
+    Target: 'output + PairSink,
+{
+    type Ok = &'output mut Target;
+    type Error = Error;
+
+    fn serialize_element<T: ?Sized + ser::Serialize>(&mut self, value: &T) -> Result<(), Error> {
+        value.serialize(pair::PairSerializer::new(self.sink, self.key_notation))
+    }
+
+    fn end(self) -> Result<Self::Ok, Error> {
+        Ok(self.sink)
+    }
+}
+
+impl<'output, Target> ser::SerializeTuple for TupleSerializer<'output, Target>
+where
+    Target: 'output + PairSink,
+{
+    type Ok = &'output mut Target;
+    type Error = Error;
+
+    fn serialize_element<T: ?Sized + ser::Serialize>(&mut self, value: &T) -> Result<(), Error> {
+        value.serialize(pair::PairSerializer::new(self.sink, self.key_notation))
+    }
+
+    fn end(self) -> Result<Self::Ok, Error> {
+        Ok(self.sink)
+    }
+}
+
+impl<'output, Target> ser::SerializeTupleStruct for TupleStructSerializer<'output, Target>
+where
+    Target: 'output + PairSink,
+{
+    type Ok = &'output mut Target;
+    type Error = Error;
+
+    fn serialize_field<T: ?Sized + ser::Serialize>(&mut self, value: &T) -> Result<(), Error> {
+        self.inner.serialize_field(value)
+    }
+
+    fn end(self) -> Result<Self::Ok, Error> {
+        self.inner.end()
+    }
+}
+
+impl<'output, Target> ser::SerializeTupleVariant for TupleVariantSerializer<'output, Target>
+where
+    Target: 'output + PairSink,
+{
+    type Ok = &'output mut Target;
+    type Error = Error;
+
+    fn serialize_field<T: ?Sized + ser::Serialize>(&mut self, value: &T) -> Result<(), Error> {
+        self.inner.serialize_field(value)
+    }
+
+    fn end(self) -> Result<Self::Ok, Error> {
+        self.inner.end()
+    }
+}
+
+impl<'output, Target> ser::SerializeMap for MapSerializer<'output, Target>
+where
+    Target: 'output + PairSink,
+{
+    type Ok = &'output mut Target;
+    type Error = Error;
+
+    fn serialize_entry<K: ?Sized + ser::Serialize, V: ?Sized + ser::Serialize>(
+        &mut self,
+        key: &K,
+        value: &V,
+    ) -> Result<(), Error> {
+        let key_sink = key::KeySink::new(|key| {
+            let value_sink = value::ValueSink::new(self.sink, &key, self.key_notation);
+            value
+                .serialize(part::PartSerializer::new(value_sink))
+                .map_err(|e| e.with_path(&key))?;
+            self.key = None;
+            Ok(())
+        });
+        let entry_serializer = part::PartSerializer::new(key_sink);
+        key.serialize(entry_serializer)
+    }
+
+    fn serialize_key<T: ?Sized + ser::Serialize>(&mut self, key: &T) -> Result<(), Error> {
+        let key_sink = key::KeySink::new(|key| key.into_owned());
+        let key_serializer = part::PartSerializer::new(key_sink);
+        self.key = Some(key.serialize(key_serializer)?);
+        Ok(())
+    }
+
+    fn serialize_value<T: ?Sized + ser::Serialize>(&mut self, value: &T) -> Result<(), Error> {
+        {
+            let key = self.key.as_ref().ok_or_else(Error::no_key)?;
+            let value_sink = value::ValueSink::new(self.sink, key, self.key_notation);
+            value.serialize(part::PartSerializer::new(value_sink)).map_err(|e| e.with_path(key))?;
+        }
+        self.key = None;
+        Ok(())
+    }
+
+    fn end(self) -> Result<Self::Ok, Error> {
+        Ok(self.sink)
+    }
+}
+
+impl<'output, Target> ser::SerializeStruct for StructSerializer<'output, Target>
+where
+    Target: 'output + PairSink,
+{
+    type Ok = &'output mut Target;
+    type Error = Error;
+
+    fn serialize_field<T: ?Sized + ser::Serialize>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), Error> {
+        let value_sink = value::ValueSink::new(self.sink, key, self.key_notation);
+        value.serialize(part::PartSerializer::new(value_sink)).map_err(|e| e.with_path(key))
+    }
+
+    fn end(self) -> Result<Self::Ok, Error> {
+        Ok(self.sink)
+    }
+}
+
+impl<'output, Target> ser::SerializeStructVariant for StructVariantSerializer<'output, Target>
+where
+    Target: 'output + PairSink,
+{
+    type Ok = &'output mut Target;
+    type Error = Error;
+
+    fn serialize_field<T: ?Sized + ser::Serialize>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), Error> {
+        self.inner.serialize_field(key, value)
+    }
+
+    fn end(self) -> Result<Self::Ok, Error> {
+        self.inner.end()
+    }
+}
@@ -1,7 +1,6 @@
-use alloc::{
-    str,
-    string::{String, ToString as _},
-};
+#[cfg(any(not(feature = "ryu"), not(feature = "alloc")))]
+use core::fmt::{self, Write as _};
+use core::str;
 
 use serde_core::ser::{self, Serializer as _};
 
@@ -20,16 +19,26 @@ impl<S: Sink> PartSerializer<S> {
 pub trait Sink: Sized {
     type Ok;
     type SerializeSeq: ser::SerializeSeq<Ok = Self::Ok, Error = Error>;
+    type SerializeMap: ser::SerializeMap<Ok = Self::Ok, Error = Error>;
+    type SerializeStruct: ser::SerializeStruct<Ok = Self::Ok, Error = Error>;
 
     fn serialize_static_str(self, value: &'static str) -> Result<Self::Ok, Error>;
 
     fn serialize_str(self, value: &str) -> Result<Self::Ok, Error>;
-    fn serialize_string(self, value: String) -> Result<Self::Ok, Error>;
     fn serialize_none(self) -> Result<Self::Ok, Error>;
 
+    fn serialize_bytes(self, value: &[u8]) -> Result<Self::Ok, Error> {
+        match str::from_utf8(value) {
+            Ok(value) => self.serialize_str(value),
+            Err(err) => Err(Error::utf8(err)),
+        }
+    }
+
     fn serialize_some<T: ?Sized + ser::Serialize>(self, value: &T) -> Result<Self::Ok, Error>;
 
     fn serialize_seq(self) -> Result<Self::SerializeSeq, Error>;
+    fn serialize_map(self) -> Result<Self::SerializeMap, Error>;
+    fn serialize_struct(self) -> Result<Self::SerializeStruct, Error>;
 
     fn unsupported(self) -> Error;
 }
@@ -41,8 +50,8 @@ impl<S: Sink> ser::Serializer for PartSerializer<S> {
     type SerializeTuple = ser::Impossible<S::Ok, Error>;
     type SerializeTupleStruct = ser::Impossible<S::Ok, Error>;
     type SerializeTupleVariant = ser::Impossible<S::Ok, Error>;
-    type SerializeMap = ser::Impossible<S::Ok, Error>;
-    type SerializeStruct = ser::Impossible<S::Ok, Error>;
+    type SerializeMap = S::SerializeMap;
+    type SerializeStruct = S::SerializeStruct;
     type SerializeStructVariant = ser::Impossible<S::Ok, Error>;
 
     fn serialize_bool(self, v: bool) -> Result<S::Ok, Error> {
@@ -94,7 +103,7 @@ impl<S: Sink> ser::Serializer for PartSerializer<S> {
         return self.serialize_floating(v);
 
         #[cfg(not(feature = "ryu"))]
-        return self.serialize_str(&v.to_string());
+        return self.serialize_displayed(&v);
     }
 
     fn serialize_f64(self, v: f64) -> Result<S::Ok, Error> {
@@ -102,11 +111,11 @@ impl<S: Sink> ser::Serializer for PartSerializer<S> {
         return self.serialize_floating(v);
 
         #[cfg(not(feature = "ryu"))]
-        return self.serialize_str(&v.to_string());
+        return self.serialize_displayed(&v);
     }
 
     fn serialize_char(self, v: char) -> Result<S::Ok, Error> {
-        self.sink.serialize_string(v.to_string())
+        self.sink.serialize_str(v.encode_utf8(&mut [0; 4]))
     }
 
     fn serialize_str(self, value: &str) -> Result<S::Ok, Error> {
@@ -114,10 +123,12 @@ impl<S: Sink> ser::Serializer for PartSerializer<S> {
     }
 
     fn serialize_bytes(self, value: &[u8]) -> Result<S::Ok, Error> {
-        match str::from_utf8(value) {
-            Ok(value) => self.sink.serialize_str(value),
-            Err(err) => Err(Error::utf8(err)),
-        }
+        self.sink.serialize_bytes(value)
+    }
+
+    #[cfg(not(feature = "alloc"))]
+    fn collect_str<T: ?Sized + fmt::Display>(self, value: &T) -> Result<S::Ok, Error> {
+        self.serialize_displayed(value)
     }
 
     fn serialize_unit(self) -> Result<S::Ok, Error> {
@@ -190,7 +201,7 @@ impl<S: Sink> ser::Serializer for PartSerializer<S> {
     }
 
     fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap, Error> {
-        Err(self.sink.unsupported())
+        self.sink.serialize_map()
     }
 
     fn serialize_struct(
@@ -198,7 +209,7 @@ impl<S: Sink> ser::Serializer for PartSerializer<S> {
         _name: &'static str,
         _len: usize,
     ) -> Result<Self::SerializeStruct, Error> {
-        Err(self.sink.unsupported())
+        self.sink.serialize_struct()
     }
 
     fn serialize_struct_variant(
@@ -229,4 +240,47 @@ impl<S: Sink> PartSerializer<S> {
         let mut buf = ryu::Buffer::new();
         self.serialize_str(buf.format(value))
     }
+
+    /// Serializes the `Display` output of a value without allocating.
+    #[cfg(any(not(feature = "ryu"), not(feature = "alloc")))]
+    fn serialize_displayed<T: ?Sized + fmt::Display>(self, value: &T) -> Result<S::Ok, Error> {
+        let mut buf = DisplayBuffer { buf: [0; DISPLAY_BUFFER_LEN], len: 0, overflowed: false };
+        if write!(buf, "{}", value).is_err() {
+            return Err(if buf.overflowed {
+                Error::value_too_long()
+            } else {
+                ser::Error::custom("a Display implementation returned an error")
+            });
+        }
+        // Only whole `str`s are written to the buffer.
+        self.serialize_str(str::from_utf8(&buf.buf[..buf.len]).unwrap_or_default())
+    }
+}
+
+/// The longest `Display` output that is serialized without allocating, which
+/// is enough for any `f64`.
+#[cfg(any(not(feature = "ryu"), not(feature = "alloc")))]
+const DISPLAY_BUFFER_LEN: usize = 400;
+
+#[cfg(any(not(feature = "ryu"), not(feature = "alloc")))]
+struct DisplayBuffer {
+    buf: [u8; DISPLAY_BUFFER_LEN],
+    len: usize,
+    overflowed: bool,
+}
+
+#[cfg(any(not(feature = "ryu"), not(feature = "alloc")))]
+impl fmt::Write for DisplayBuffer {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        let end = self.len + s.len();
+        match self.buf.get_mut(self.len..end) {
+            Some(buf) => buf.copy_from_slice(s.as_bytes()),
+            None => {
+                self.overflowed = true;
+                return Err(fmt::Error);
+            }
+        }
+        self.len = end;
+        Ok(())
+    }
 }
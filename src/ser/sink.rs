@@ -0,0 +1,203 @@
+#[cfg(feature = "alloc")]
+use alloc::{string::String, vec::Vec};
+use core::{fmt, str};
+
+#[cfg(feature = "alloc")]
+use form_urlencoded::{Serializer as UrlEncodedSerializer, Target as UrlEncodedTarget};
+
+use super::Error;
+
+/// A destination for the pairs written by a [`Serializer`](super::pairs::Serializer).
+///
+/// Keys and values are passed as they are, before any percent-encoding. This
+/// is implemented for `form_urlencoded::Serializer`, for `Vec<(String,
+/// String)>` and for [`Form`](crate::Form), and can be implemented for other
+/// collections of pairs.
+pub trait PairSink {
+    /// Appends a pair.
+    fn append_pair(&mut self, key: &str, value: &str) -> Result<(), Error>;
+
+    /// Appends a pair whose value is arbitrary bytes.
+    ///
+    /// The default implementation appends the value as a string if it is
+    /// valid UTF-8, and returns an error otherwise.
+    fn append_bytes_pair(&mut self, key: &str, value: &[u8]) -> Result<(), Error> {
+        match str::from_utf8(value) {
+            Ok(value) => self.append_pair(key, value),
+            Err(err) => Err(Error::utf8(err)),
+        }
+    }
+}
+
+/// Values that are not valid UTF-8 can not be written by a
+/// `form_urlencoded::Serializer`, so they result in an error.
+#[cfg(feature = "alloc")]
+impl<Target: UrlEncodedTarget> PairSink for UrlEncodedSerializer<'_, Target> {
+    fn append_pair(&mut self, key: &str, value: &str) -> Result<(), Error> {
+        UrlEncodedSerializer::append_pair(self, key, value);
+        Ok(())
+    }
+}
+
+/// Pairs are appended without percent-encoding. Values that are not valid
+/// UTF-8 result in an error.
+#[cfg(feature = "alloc")]
+impl PairSink for Vec<(String, String)> {
+    fn append_pair(&mut self, key: &str, value: &str) -> Result<(), Error> {
+        self.push((key.into(), value.into()));
+        Ok(())
+    }
+}
+
+/// A `application/x-www-form-urlencoded` encoder that writes to a
+/// `fmt::Write`, usually a `String`.
+///
+/// Unlike `form_urlencoded::Serializer`, this can percent-encode values that
+/// are not valid UTF-8.
+pub(crate) struct Encoder<W> {
+    target: W,
+    /// Whether a pair was written, so the next one needs a `&` separator.
+    needs_separator: bool,
+    #[cfg(feature = "encoding")]
+    encoding: Option<&'static encoding_rs::Encoding>,
+}
+
+impl<W: fmt::Write> Encoder<W> {
+    /// Returns an encoder that appends to `target`, with `&` separators
+    /// between the pairs it writes.
+    pub fn new(target: W) -> Self {
+        Encoder {
+            target,
+            needs_separator: false,
+            #[cfg(feature = "encoding")]
+            encoding: None,
+        }
+    }
+
+    /// Sets the encoding that names and values are encoded in before
+    /// percent-encoding.
+    #[cfg(feature = "encoding")]
+    pub fn encoding(&mut self, encoding: &'static encoding_rs::Encoding) {
+        self.encoding = Some(encoding);
+    }
+
+    fn write_encoded_pair(&mut self, key: &[u8], value: &[u8]) -> Result<(), Error> {
+        self.write_pair(key, value).map_err(|_| Error::write())
+    }
+
+    fn write_pair(&mut self, key: &[u8], value: &[u8]) -> fmt::Result {
+        if self.needs_separator {
+            self.target.write_char('&')?;
+        }
+        self.needs_separator = true;
+        byte_serialize(&mut self.target, key)?;
+        self.target.write_char('=')?;
+        byte_serialize(&mut self.target, value)
+    }
+}
+
+impl<W: fmt::Write> PairSink for Encoder<W> {
+    fn append_pair(&mut self, key: &str, value: &str) -> Result<(), Error> {
+        #[cfg(feature = "encoding")]
+        if let Some(encoding) = self.encoding {
+            return self.write_encoded_pair(&encoding.encode(key).0, &encoding.encode(value).0);
+        }
+
+        self.write_encoded_pair(key.as_bytes(), value.as_bytes())
+    }
+
+    fn append_bytes_pair(&mut self, key: &str, value: &[u8]) -> Result<(), Error> {
+        #[cfg(feature = "encoding")]
+        if let Some(encoding) = self.encoding {
+            return self.write_encoded_pair(&encoding.encode(key).0, value);
+        }
+
+        self.write_encoded_pair(key.as_bytes(), value)
+    }
+}
+
+/// Percent-encodes `input` like `form_urlencoded::byte_serialize`, which is
+/// not available without an allocator.
+fn byte_serialize(target: &mut impl fmt::Write, input: &[u8]) -> fmt::Result {
+    const HEX: &[u8; 16] = b"0123456789ABCDEF";
+
+    let mut rest = input;
+    loop {
+        let len = rest.iter().take_while(|&&b| is_unreserved(b)).count();
+        let (unreserved, tail) = rest.split_at(len);
+        // Unreserved bytes are ASCII.
+        target.write_str(str::from_utf8(unreserved).unwrap_or_default())?;
+
+        let (b, tail) = match tail {
+            [b, tail @ ..] => (*b, tail),
+            [] => return Ok(()),
+        };
+        if b == b' ' {
+            target.write_char('+')?;
+        } else {
+            target.write_char('%')?;
+            target.write_char(HEX[usize::from(b >> 4)].into())?;
+            target.write_char(HEX[usize::from(b & 0xF)].into())?;
+        }
+        rest = tail;
+    }
+}
+
+fn is_unreserved(b: u8) -> bool {
+    matches!(b, b'*' | b'-' | b'.' | b'_' | b'0'..=b'9' | b'A'..=b'Z' | b'a'..=b'z')
+}
+
+/// A `fmt::Write` into a byte slice, for `to_slice`.
+pub(crate) struct SliceWriter<'a> {
+    buf: &'a mut [u8],
+    len: usize,
+    full: bool,
+}
+
+impl<'a> SliceWriter<'a> {
+    pub fn new(buf: &'a mut [u8]) -> Self {
+        SliceWriter { buf, len: 0, full: false }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.full
+    }
+}
+
+impl fmt::Write for SliceWriter<'_> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        let end = self.len + s.len();
+        match self.buf.get_mut(self.len..end) {
+            Some(buf) => {
+                buf.copy_from_slice(s.as_bytes());
+                self.len = end;
+                Ok(())
+            }
+            None => {
+                self.full = true;
+                Err(fmt::Error)
+            }
+        }
+    }
+}
+
+/// Adapts an `io::Write` to `fmt::Write`, keeping the last I/O error.
+#[cfg(feature = "std")]
+pub(crate) struct IoWriter<W> {
+    pub inner: W,
+    pub error: Option<std::io::Error>,
+}
+
+#[cfg(feature = "std")]
+impl<W: std::io::Write> fmt::Write for IoWriter<W> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.inner.write_all(s.as_bytes()).map_err(|e| {
+            self.error = Some(e);
+            fmt::Error
+        })
+    }
+}
@@ -1,6 +1,8 @@
-use alloc::{borrow::ToOwned as _, string::String, vec, vec::Vec};
-use insta::assert_snapshot;
-use serde::Serialize;
+use alloc::{borrow::ToOwned as _, collections::BTreeMap, string::String, vec, vec::Vec};
+use insta::{assert_compact_debug_snapshot, assert_snapshot};
+use serde::{Deserialize as _, Serialize};
+
+use crate::KeyNotation;
 
 #[derive(Serialize)]
 struct NewType<T>(T);
@@ -185,8 +187,350 @@ fn serialize_list_of_enum() {
 
 #[test]
 fn serialize_map() {
-    let mut s = alloc::collections::BTreeMap::new();
+    let mut s = BTreeMap::new();
     s.insert("a", "hello");
     s.insert("b", "world");
     assert_snapshot!(super::to_string(s).unwrap(), @"a=hello&b=world");
 }
+
+fn to_string_brackets<T: Serialize>(input: T) -> Result<String, super::Error> {
+    let mut target = String::new();
+    let mut urlencoder = form_urlencoded::Serializer::new(&mut target);
+    input.serialize(super::Serializer::new(&mut urlencoder).key_notation(KeyNotation::Brackets))?;
+    Ok(target)
+}
+
+fn to_string_dots<T: Serialize>(input: T) -> Result<String, super::Error> {
+    let mut target = String::new();
+    let mut urlencoder = form_urlencoded::Serializer::new(&mut target);
+    input.serialize(super::Serializer::new(&mut urlencoder).key_notation(KeyNotation::Dots))?;
+    Ok(target)
+}
+
+#[derive(Serialize)]
+struct Address {
+    city: &'static str,
+    zip: Option<u32>,
+}
+
+#[test]
+fn serialize_nested_struct_brackets() {
+    #[derive(Serialize)]
+    struct Form {
+        id: u32,
+        address: Address,
+    }
+
+    let params = Form { id: 1, address: Address { city: "Berlin", zip: Some(10115) } };
+    assert_snapshot!(
+        to_string_brackets(params).unwrap(),
+        @"id=1&address%5Bcity%5D=Berlin&address%5Bzip%5D=10115"
+    );
+}
+
+#[test]
+fn serialize_nested_map_brackets() {
+    let mut filters = BTreeMap::new();
+    filters.insert("status", vec!["open", "closed"]);
+    filters.insert("owner", vec!["me"]);
+
+    let params = &[("filters", filters)];
+    assert_snapshot!(
+        to_string_brackets(params).unwrap(),
+        @"filters%5Bowner%5D=me&filters%5Bstatus%5D=open&filters%5Bstatus%5D=closed"
+    );
+}
+
+#[test]
+fn serialize_deeply_nested_brackets() {
+    let params = Wrapper { item: Some(Wrapper { item: Address { city: "Paris", zip: None } }) };
+    assert_snapshot!(to_string_brackets(params).unwrap(), @"item%5Bitem%5D%5Bcity%5D=Paris");
+}
+
+#[test]
+fn serialize_nested_map_separator_keys() {
+    let mut map = BTreeMap::new();
+    map.insert("a]b", "1");
+    let error = to_string_brackets([("m", &map)]).unwrap_err();
+    assert_eq!(error.kind(), super::ErrorKind::UnsupportedKey);
+    assert_snapshot!(error, @"unsupported key");
+
+    for key in ["a.b", "a]b", ""] {
+        let map = BTreeMap::from([(key, "1")]);
+        let error = to_string_dots([("m", &map)]).unwrap_err();
+        assert_eq!(error.kind(), super::ErrorKind::UnsupportedKey);
+    }
+
+    let mut map = BTreeMap::new();
+    map.insert("a.b", "1");
+    assert_snapshot!(to_string_brackets([("m", &map)]).unwrap(), @"m%5Ba.b%5D=1");
+
+    let mut map = BTreeMap::new();
+    map.insert("", "1");
+    let error = to_string_brackets([("m", &map)]).unwrap_err();
+    assert_eq!(error.kind(), super::ErrorKind::UnsupportedKey);
+}
+
+#[test]
+fn serialize_nested_map_brackets_round_trip() {
+    let mut map = BTreeMap::new();
+    map.insert("a.b".to_owned(), "1".to_owned());
+    map.insert("c d&e=f".to_owned(), "2".to_owned());
+    map.insert("é".to_owned(), "3".to_owned());
+    let params = BTreeMap::from([("m".to_owned(), map)]);
+
+    let output = to_string_brackets(&params).unwrap();
+    assert_snapshot!(output, @"m%5Ba.b%5D=1&m%5Bc+d%26e%3Df%5D=2&m%5B%C3%A9%5D=3");
+    let deserializer = crate::Deserializer::from_bytes(output.as_bytes());
+    let result = BTreeMap::deserialize(deserializer.key_notation(KeyNotation::Brackets));
+    assert_eq!(result, Ok(params));
+}
+
+#[test]
+fn serialize_nested_map_dots_round_trip() {
+    let mut map = BTreeMap::new();
+    map.insert("a b".to_owned(), "1".to_owned());
+    map.insert("c&d=e".to_owned(), "2".to_owned());
+    map.insert("é".to_owned(), "3".to_owned());
+    let params = BTreeMap::from([("m".to_owned(), map)]);
+
+    let output = to_string_dots(&params).unwrap();
+    assert_snapshot!(output, @"m.a+b=1&m.c%26d%3De=2&m.%C3%A9=3");
+    let deserializer = crate::Deserializer::from_bytes(output.as_bytes());
+    let result = BTreeMap::deserialize(deserializer.key_notation(KeyNotation::Dots));
+    assert_eq!(result, Ok(params));
+}
+
+#[test]
+fn serialize_nested_struct_flat() {
+    let params = Wrapper { item: Address { city: "Berlin", zip: None } };
+    assert_snapshot!(super::to_string(params).unwrap_err(), @"unsupported value");
+}
+
+#[test]
+fn serialize_nested_struct_dots() {
+    let mut target = String::new();
+    let mut urlencoder = form_urlencoded::Serializer::new(&mut target);
+    let params = Wrapper { item: Address { city: "Berlin", zip: Some(10115) } };
+    params
+        .serialize(super::Serializer::new(&mut urlencoder).key_notation(KeyNotation::Dots))
+        .unwrap();
+    assert_snapshot!(target, @"item.city=Berlin&item.zip=10115");
+}
+
+#[derive(Serialize)]
+struct LineItem {
+    sku: &'static str,
+    qty: u32,
+}
+
+#[test]
+fn serialize_indexed_structs_brackets() {
+    let params =
+        Wrapper { item: vec![LineItem { sku: "A", qty: 2 }, LineItem { sku: "B", qty: 1 }] };
+    assert_snapshot!(
+        to_string_brackets(params).unwrap(),
+        @"item%5B0%5D%5Bsku%5D=A&item%5B0%5D%5Bqty%5D=2&item%5B1%5D%5Bsku%5D=B&item%5B1%5D%5Bqty%5D=1"
+    );
+}
+
+#[test]
+fn serialize_indexed_structs_dots() {
+    let mut target = String::new();
+    let mut urlencoder = form_urlencoded::Serializer::new(&mut target);
+    let params =
+        Wrapper { item: vec![LineItem { sku: "A", qty: 2 }, LineItem { sku: "B", qty: 1 }] };
+    params
+        .serialize(super::Serializer::new(&mut urlencoder).key_notation(KeyNotation::Dots))
+        .unwrap();
+    assert_snapshot!(target, @"item%5B0%5D.sku=A&item%5B0%5D.qty=2&item%5B1%5D.sku=B&item%5B1%5D.qty=1");
+}
+
+#[test]
+fn serialize_nested_list_brackets() {
+    let params = &[("list", vec![vec![0_u8, 1], vec![2]])];
+    assert_snapshot!(to_string_brackets(params).unwrap(), @"list%5B0%5D=0&list%5B0%5D=1&list%5B1%5D=2");
+}
+
+#[test]
+fn serialize_byte_fields() {
+    let params = &[("signature", serde_bytes::Bytes::new(b"\xFF\x00\xC3\xA9 a"))];
+    assert_snapshot!(super::to_string(params).unwrap(), @"signature=%FF%00%C3%A9+a");
+
+    let mut output = String::new();
+    let mut urlencoder = form_urlencoded::Serializer::new(&mut output);
+    let result = params.serialize(super::Serializer::new(&mut urlencoder));
+    assert_snapshot!(result.map(drop).unwrap_err(), @"invalid UTF-8: invalid utf-8 sequence of 1 bytes from index 0");
+
+    let params = &[("token", serde_bytes::Bytes::new(b"ok"))];
+    let mut urlencoder = form_urlencoded::Serializer::new(String::new());
+    params.serialize(super::Serializer::new(&mut urlencoder)).unwrap();
+    assert_snapshot!(urlencoder.finish(), @"token=ok");
+}
+
+#[test]
+fn serialize_error_details() {
+    use super::ErrorKind;
+
+    #[derive(Serialize)]
+    struct Profile {
+        name: &'static str,
+        address: Address,
+        tags: Vec<Vec<&'static str>>,
+    }
+
+    #[derive(Serialize)]
+    struct Address {
+        city: (u8, u8),
+    }
+
+    let error = super::to_string(&[("list", vec![vec![0_u8]])]).unwrap_err();
+    assert_eq!(error.kind(), ErrorKind::UnsupportedValue);
+    assert_eq!(error.path(), Some("list"));
+
+    let profile = Profile { name: "Jane", address: Address { city: (1, 2) }, tags: vec![] };
+    let error = super::to_string(&profile).unwrap_err();
+    assert_eq!(error.kind(), ErrorKind::UnsupportedValue);
+    assert_eq!(error.path(), Some("address"));
+
+    let error = to_string_brackets(&profile).unwrap_err();
+    assert_eq!(error.kind(), ErrorKind::UnsupportedValue);
+    assert_eq!(error.path(), Some("address[city]"));
+
+    let mut map = BTreeMap::new();
+    map.insert("ok", vec![]);
+    map.insert("nested", vec![vec![1]]);
+    let error = super::to_string(map).unwrap_err();
+    assert_eq!(error.path(), Some("nested"));
+
+    let error = super::to_string(&[(vec![1], 1)]).unwrap_err();
+    assert_eq!(error.kind(), ErrorKind::UnsupportedKey);
+    assert_eq!(error.path(), None);
+
+    let error = super::to_string(1).unwrap_err();
+    assert_eq!(error.kind(), ErrorKind::TopLevel);
+    assert_eq!(error.path(), None);
+}
+
+#[cfg(feature = "encoding")]
+#[test]
+fn serialize_with_encoding() {
+    let params = &[("name", "Renée"), ("city", "東京"), ("note", "€ & ✓")];
+    assert_snapshot!(
+        super::to_string_with_encoding(params, encoding_rs::WINDOWS_1252).unwrap(),
+        @"name=Ren%E9e&city=%26%2326481%3B%26%2320140%3B&note=%80+%26+%26%2310003%3B"
+    );
+    assert_snapshot!(
+        super::to_string_with_encoding(params, encoding_rs::SHIFT_JIS).unwrap(),
+        @"name=Ren%26%23233%3Be&city=%93%8C%8B%9E&note=%26%238364%3B+%26+%26%2310003%3B"
+    );
+    assert_snapshot!(
+        super::to_string_with_encoding(params, encoding_rs::UTF_16LE).unwrap(),
+        @"name=Ren%C3%A9e&city=%E6%9D%B1%E4%BA%AC&note=%E2%82%AC+%26+%E2%9C%93"
+    );
+}
+
+#[test]
+fn serialize_to_pairs() {
+    #[derive(Serialize)]
+    enum Sort {
+        Newest,
+    }
+
+    #[derive(Serialize)]
+    struct Search {
+        q: &'static str,
+        page: Option<u32>,
+        sort: Sort,
+        tag: Vec<&'static str>,
+        address: Address,
+    }
+
+    let search = Search {
+        q: "a&b=c d",
+        page: None,
+        sort: Sort::Newest,
+        tag: vec!["x", "y"],
+        address: Address { city: "Berlin", zip: Some(10115) },
+    };
+    assert_compact_debug_snapshot!(
+        super::to_pairs(&search).map_err(|e| e.kind()),
+        @"Err(UnsupportedValue)"
+    );
+
+    let mut pairs = Vec::new();
+    search
+        .serialize(super::pairs::Serializer::new(&mut pairs).key_notation(KeyNotation::Brackets))
+        .unwrap();
+    assert_compact_debug_snapshot!(
+        pairs,
+        @r#"[("q", "a&b=c d"), ("sort", "Newest"), ("tag", "x"), ("tag", "y"), ("address[city]", "Berlin"), ("address[zip]", "10115")]"#
+    );
+
+    let params = &[("signature", serde_bytes::Bytes::new(b"\xFF"))];
+    assert_snapshot!(super::to_pairs(params).unwrap_err(), @"invalid UTF-8: invalid utf-8 sequence of 1 bytes from index 0");
+
+    let form: crate::Form =
+        super::to_pairs([("a", 1), ("b", 2), ("a", 3)]).unwrap().into_iter().collect();
+    assert_eq!(form.get_all("a").collect::<Vec<_>>(), ["1", "3"]);
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn serialize_to_writers() {
+    use core::fmt::Write as _;
+    use std::{error::Error as _, io};
+
+    struct Broken;
+
+    impl io::Write for Broken {
+        fn write(&mut self, _buf: &[u8]) -> io::Result<usize> {
+            Err(io::Error::new(io::ErrorKind::Other, "disk full"))
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    let params = &[("a", "1"), ("b", "é ")];
+    let mut output = Vec::new();
+    super::to_writer(&mut output, params).unwrap();
+    assert_snapshot!(String::from_utf8(output).unwrap(), @"a=1&b=%C3%A9+");
+
+    let error = super::to_writer(Broken, params).unwrap_err();
+    assert_eq!(error.kind(), super::ErrorKind::Write);
+    assert_snapshot!(error, @"failed to write output: disk full");
+    assert_snapshot!(error.source().unwrap(), @"disk full");
+
+    let mut output = String::from("x=0");
+    write!(output, "&{}", super::display(params)).unwrap();
+    assert_snapshot!(output, @"x=0&a=1&b=%C3%A9+");
+
+    let mut output = String::new();
+    assert!(write!(output, "{}", super::display(&[("list", vec![vec![0_u8]])])).is_err());
+
+    #[cfg(feature = "bytes")]
+    {
+        let mut output = bytes::BytesMut::from("x=0&");
+        super::push_to_bytes_mut(&mut output, params).unwrap();
+        assert_eq!(&output[..], b"x=0&a=1&b=%C3%A9+");
+    }
+}
+
+#[test]
+fn serialize_to_slice() {
+    let mut buf = [0; 32];
+    let len = super::to_slice(&mut buf, [("emoji", '😀'), ("x", ' ')]).unwrap();
+    assert_snapshot!(core::str::from_utf8(&buf[..len]).unwrap(), @"emoji=%F0%9F%98%80&x=+");
+
+    let len = super::to_slice(&mut buf[..3], ()).unwrap();
+    assert_eq!(len, 0);
+
+    let error = super::to_slice(&mut buf[..10], [("emoji", '😀')]).unwrap_err();
+    assert_eq!(error.kind(), super::ErrorKind::BufferFull);
+    assert_snapshot!(error, @"buffer full");
+
+    let error = super::to_slice(&mut buf, [("a", [[1]])]).unwrap_err();
+    assert_eq!(error.kind(), super::ErrorKind::UnsupportedValue);
+}
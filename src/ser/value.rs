@@ -1,52 +1,80 @@
-use alloc::{str, string::String};
+// Without `alloc`, `ValueKey` is a plain `&str`, so some conversions and
+// borrows that are needed for `Cow` become no-ops.
+#![cfg_attr(
+    not(feature = "alloc"),
+    allow(clippy::borrow_deref_ref, clippy::needless_borrow, clippy::useless_conversion)
+)]
 
-use form_urlencoded::{Serializer as UrlEncodedSerializer, Target as UrlEncodedTarget};
+#[cfg(feature = "alloc")]
+use alloc::borrow::Cow;
+
+#[cfg(not(feature = "alloc"))]
+use serde_core::ser::Impossible;
 use serde_core::ser::{Serialize, SerializeSeq};
 
+#[cfg(feature = "alloc")]
+use super::nested::{indexed_key, NestedSerializer};
 use super::{
     part::{PartSerializer, Sink},
+    sink::PairSink,
     Error,
 };
+use crate::KeyNotation;
+
+/// The key of a value, which is only built at runtime for indexed keys.
+#[cfg(feature = "alloc")]
+type ValueKey<'key> = Cow<'key, str>;
+
+#[cfg(not(feature = "alloc"))]
+type ValueKey<'key> = &'key str;
 
-pub struct ValueSink<'input, 'key, 'target, Target>
+pub struct ValueSink<'key, 'target, Target>
 where
-    Target: UrlEncodedTarget,
+    Target: PairSink,
 {
-    urlencoder: &'target mut UrlEncodedSerializer<'input, Target>,
-    key: &'key str,
-    nested: bool,
+    sink: &'target mut Target,
+    key: ValueKey<'key>,
+    key_notation: KeyNotation,
+    /// The index of this value, if it is an element of a sequence.
+    index: Option<usize>,
+    /// The number of elements serialized so far, if this is a sequence.
+    len: usize,
 }
 
-impl<'input, 'key, 'target, Target> ValueSink<'input, 'key, 'target, Target>
+impl<'key, 'target, Target> ValueSink<'key, 'target, Target>
 where
-    Target: 'target + UrlEncodedTarget,
+    Target: 'target + PairSink,
 {
-    pub fn new(
-        urlencoder: &'target mut UrlEncodedSerializer<'input, Target>,
-        key: &'key str,
-    ) -> Self {
-        ValueSink { urlencoder, key, nested: false }
+    pub fn new(sink: &'target mut Target, key: &'key str, key_notation: KeyNotation) -> Self {
+        ValueSink { sink, key: key.into(), key_notation, index: None, len: 0 }
     }
 }
 
-impl<'target, Target> Sink for ValueSink<'_, '_, 'target, Target>
+impl<'key, 'target, Target> Sink for ValueSink<'key, 'target, Target>
 where
-    Target: 'target + UrlEncodedTarget,
+    Target: 'target + PairSink,
 {
     type Ok = ();
     type SerializeSeq = Self;
+    #[cfg(feature = "alloc")]
+    type SerializeMap = NestedSerializer<'key, 'target, Target>;
+    #[cfg(feature = "alloc")]
+    type SerializeStruct = NestedSerializer<'key, 'target, Target>;
+    #[cfg(not(feature = "alloc"))]
+    type SerializeMap = Impossible<(), Error>;
+    #[cfg(not(feature = "alloc"))]
+    type SerializeStruct = Impossible<(), Error>;
 
     fn serialize_str(self, value: &str) -> Result<(), Error> {
-        self.urlencoder.append_pair(self.key, value);
-        Ok(())
+        self.sink.append_pair(&self.key, value)
     }
 
     fn serialize_static_str(self, value: &'static str) -> Result<(), Error> {
         self.serialize_str(value)
     }
 
-    fn serialize_string(self, value: String) -> Result<(), Error> {
-        self.serialize_str(&value)
+    fn serialize_bytes(self, value: &[u8]) -> Result<(), Error> {
+        self.sink.append_bytes_pair(&self.key, value)
     }
 
     fn serialize_none(self) -> Result<Self::Ok, Error> {
@@ -58,21 +86,67 @@ where
     }
 
     fn serialize_seq(self) -> Result<Self, Error> {
-        if self.nested {
-            Err(self.unsupported())
-        } else {
-            Ok(self)
+        match (self.key_notation, self.index) {
+            (_, None) => Ok(self),
+            (KeyNotation::Flat, Some(_)) => Err(self.unsupported()),
+            #[cfg(not(feature = "alloc"))]
+            (_, Some(_)) => Err(self.unsupported()),
+            #[cfg(feature = "alloc")]
+            (key_notation, Some(index)) => Ok(ValueSink {
+                sink: self.sink,
+                key: indexed_key(&self.key, index).into(),
+                key_notation,
+                index: None,
+                len: 0,
+            }),
         }
     }
 
+    #[cfg(feature = "alloc")]
+    fn serialize_map(self) -> Result<Self::SerializeMap, Error> {
+        self.serialize_nested()
+    }
+
+    #[cfg(feature = "alloc")]
+    fn serialize_struct(self) -> Result<Self::SerializeStruct, Error> {
+        self.serialize_nested()
+    }
+
+    /// Nested keys are built at runtime, so they need the `alloc` feature.
+    #[cfg(not(feature = "alloc"))]
+    fn serialize_map(self) -> Result<Self::SerializeMap, Error> {
+        Err(self.unsupported())
+    }
+
+    #[cfg(not(feature = "alloc"))]
+    fn serialize_struct(self) -> Result<Self::SerializeStruct, Error> {
+        Err(self.unsupported())
+    }
+
     fn unsupported(self) -> Error {
         Error::unsupported_value()
     }
 }
 
-impl<'target, Target> SerializeSeq for ValueSink<'_, '_, 'target, Target>
+#[cfg(feature = "alloc")]
+impl<'key, 'target, Target> ValueSink<'key, 'target, Target>
 where
-    Target: 'target + UrlEncodedTarget,
+    Target: 'target + PairSink,
+{
+    fn serialize_nested(self) -> Result<NestedSerializer<'key, 'target, Target>, Error> {
+        let prefix = match (self.key_notation, self.index) {
+            (KeyNotation::Flat, _) => return Err(self.unsupported()),
+            (_, None) => self.key,
+            (_, Some(index)) => indexed_key(&self.key, index).into(),
+        };
+
+        Ok(NestedSerializer::new(self.sink, prefix, self.key_notation))
+    }
+}
+
+impl<'target, Target> SerializeSeq for ValueSink<'_, 'target, Target>
+where
+    Target: 'target + PairSink,
 {
     type Ok = ();
     type Error = Error;
@@ -81,10 +155,15 @@ where
     where
         T: Serialize + ?Sized,
     {
+        let index = self.len;
+        self.len += 1;
+
         value.serialize(PartSerializer::new(ValueSink {
-            urlencoder: self.urlencoder,
-            key: self.key,
-            nested: true,
+            sink: self.sink,
+            key: (&*self.key).into(),
+            key_notation: self.key_notation,
+            index: Some(index),
+            len: 0,
         }))
     }
 